@@ -1,30 +1,166 @@
-use crate::token::{CompileError, MetadataReference, Source, Token, TokenKind, TokenStream};
+use std::path::Path;
 
-#[derive(Debug)]
+use crate::{
+    constant::{SOURCE_FILE_EXTENSION, SYSTEM_LIB_ROOT},
+    token::{CompileError, MetadataReference, Token, TokenKind, TokenStream},
+};
+
+#[derive(Debug, Clone)]
 pub struct BinaryOP {
-    left: Box<ASTnode>,
-    right: Box<ASTnode>,
-    metadata: MetadataReference,
+    pub left: Box<ASTnode>,
+    pub right: Box<ASTnode>,
+    pub metadata: MetadataReference,
+}
+/// how a cast changes the bits of its value, chosen by semantic analysis
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    /// the bits are used as they are, same width integers and pointers
+    Keep,
+    ZeroExtend,
+    SignExtend,
+    Truncate,
+    IntToFloat {
+        signed: bool,
+    },
+    FloatToInt {
+        signed: bool,
+    },
+}
+/// how an asm block uses a register
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsmDirection {
+    /// loaded with the value before the block
+    In,
+    /// stored to the value after the block
+    Out,
+    /// both loaded and stored
+    InOut,
+    /// overwritten by the block, not bound to a value
+    Clobber,
+}
+/// operator of a compound assignment, `a += b`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignOperator {
+    Add,
+    Sub,
+    Mult,
+    Div,
+    Mod,
+    And,
+    Or,
+    Xor,
+    ShiftLeft,
+    ShiftRight,
+    /// `>>=` on a signed value, chosen by semantic analysis
+    ArithmeticShiftRight,
 }
-#[derive(Debug)]
+// fields that are only read when lowering to assembly are marked `allow(dead_code)`
+#[derive(Debug, Clone)]
 pub enum ASTnode {
+    /// a source file, `modules`, `imports` and `impls` are merged into the items by module
+    /// resolution
     Root {
+        types: Vec<ASTnode>,
         globals: Vec<ASTnode>,
         functions: Vec<ASTnode>,
+        modules: Vec<ASTnode>,
+        imports: Vec<ASTnode>,
+        impls: Vec<ASTnode>,
+    },
+    /// [pub] mod name; the Root parsed from name.ansl
+    Module {
+        name: String,
+        public: bool,
+        root: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// use a::b::item [as alias];
+    Use {
+        path: Vec<String>,
+        alias: Option<String>,
+        metadata: MetadataReference,
+    },
+    /// impl Type { fn ... const ... }, the methods and associated consts of a struct or enum,
+    /// lowered by module resolution to items labelled `Type$name`
+    Impl {
+        kind: Box<ASTnode>,
+        items: Vec<ASTnode>,
+        metadata: MetadataReference,
     },
     Type {
         name: String,
         metadata: MetadataReference,
     },
+    /// *T or &T
+    PointerType {
+        kind: Box<ASTnode>,
+        #[allow(dead_code)]
+        metadata: MetadataReference,
+    },
+    /// fn(A, B) -> T, a pointer to a function, `variadic` when the last parameter is `...`
+    FunctionType {
+        params: Vec<ASTnode>,
+        return_type: Option<Box<ASTnode>>,
+        variadic: bool,
+        #[allow(dead_code)]
+        metadata: MetadataReference,
+    },
+    /// (A, B, ...), `()` is the unit type
+    TupleType {
+        elements: Vec<ASTnode>,
+        #[allow(dead_code)]
+        metadata: MetadataReference,
+    },
+    /// [T; N]
+    ArrayType {
+        kind: Box<ASTnode>,
+        length: usize,
+        #[allow(dead_code)]
+        metadata: MetadataReference,
+    },
+    /// `addressed` is set by semantic analysis when the address of the variable is taken,
+    /// forcing it onto the stack
     Variable {
         name: String,
         kind: Box<ASTnode>,
+        addressed: bool,
+        #[allow(dead_code)]
+        metadata: MetadataReference,
+    },
+    Identifier {
+        name: String,
+        metadata: MetadataReference,
+    },
+    NumberLiteral {
+        value: usize,
+        metadata: MetadataReference,
+    },
+    StringLiteral {
+        value: String,
+        metadata: MetadataReference,
+    },
+    CharLiteral {
+        value: char,
+        metadata: MetadataReference,
+    },
+    BoolLiteral {
+        value: bool,
+        metadata: MetadataReference,
+    },
+    ArrayLiteral {
+        elements: Vec<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// [value; N]
+    ArrayRepeat {
+        value: Box<ASTnode>,
+        length: usize,
+        metadata: MetadataReference,
+    },
+    TupleLiteral {
+        elements: Vec<ASTnode>,
         metadata: MetadataReference,
     },
-    NumberLiteral(usize),
-    StringLiteral(String),
-    ArrayLiteral(Vec<ASTnode>),
-    TupleLiteral(Vec<ASTnode>),
     FunctionCall {
         name: String,
         params: Vec<ASTnode>,
@@ -33,20 +169,237 @@ pub enum ASTnode {
     Block {
         nodes: Vec<ASTnode>,
     },
+    /// enum Name: type { Variant = value, Variant(type, ...), ... }
+    /// `kind` is the integer type of the tag holding the discriminant, when not given it is chosen
+    /// by semantic analysis which also fills in `size` and `align`
+    EnumDeclaration {
+        name: String,
+        public: bool,
+        kind: Option<Box<ASTnode>>,
+        variants: Vec<ASTnode>,
+        size: usize,
+        align: usize,
+        metadata: MetadataReference,
+    },
+    /// `discriminant` is filled in by semantic analysis, from `value` when given or else one
+    /// more than the previous variant. `fields` is the payload, one Field per value named by
+    /// its position
+    EnumVariant {
+        name: String,
+        value: Option<Box<ASTnode>>,
+        fields: Vec<ASTnode>,
+        discriminant: i128,
+        metadata: MetadataReference,
+    },
+    /// Enum::Variant(binding, _, ...) in a match arm
+    VariantPattern {
+        segments: Vec<String>,
+        bindings: Vec<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// callee(params), for callees that are not a plain function name
+    Call {
+        callee: Box<ASTnode>,
+        params: Vec<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// Enum::Variant
+    Path {
+        segments: Vec<String>,
+        metadata: MetadataReference,
+    },
+    /// `size`, `align` and the offsets of `fields` are filled in by semantic analysis
+    StructDeclaration {
+        name: String,
+        public: bool,
+        fields: Vec<ASTnode>,
+        packed: bool,
+        size: usize,
+        align: usize,
+        metadata: MetadataReference,
+    },
+    Field {
+        name: String,
+        kind: Box<ASTnode>,
+        offset: usize,
+        metadata: MetadataReference,
+    },
+    /// Name { field: expr, ... }
+    StructLiteral {
+        name: String,
+        fields: Vec<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// field: expr, inside of a StructLiteral
+    FieldValue {
+        name: String,
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// value.field
+    FieldAccess {
+        value: Box<ASTnode>,
+        field: String,
+        metadata: MetadataReference,
+    },
+    /// `else_body` is either a Block or, for `else if`, another If
+    If {
+        condition: Box<ASTnode>,
+        body: Box<ASTnode>,
+        else_body: Option<Box<ASTnode>>,
+        metadata: MetadataReference,
+    },
+    While {
+        condition: Box<ASTnode>,
+        body: Box<ASTnode>,
+        label: Option<String>,
+        metadata: MetadataReference,
+    },
+    /// loop { }, only left through `break` or `return`
+    Loop {
+        body: Box<ASTnode>,
+        label: Option<String>,
+        metadata: MetadataReference,
+    },
+    /// over a Range this is a counted loop, `end` and `step` are evaluated once before the first
+    /// iteration. `reverse` walks the range from its last value down to `start`
+    For {
+        variable: String,
+        iterable: Box<ASTnode>,
+        #[allow(dead_code)]
+        reverse: bool,
+        step: Option<Box<ASTnode>>,
+        body: Box<ASTnode>,
+        label: Option<String>,
+        metadata: MetadataReference,
+    },
+    /// `jump_table` is set by semantic analysis when the arms are dense enough to be dispatched
+    /// through a table of labels rather than a chain of compares
+    Match {
+        value: Box<ASTnode>,
+        arms: Vec<ASTnode>,
+        jump_table: bool,
+        metadata: MetadataReference,
+    },
+    /// pattern => body
+    MatchArm {
+        pattern: Box<ASTnode>,
+        body: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// the `_` pattern
+    Wildcard {
+        metadata: MetadataReference,
+    },
+    /// start..end or start..=end
+    Range {
+        start: Box<ASTnode>,
+        end: Box<ASTnode>,
+        inclusive: bool,
+        metadata: MetadataReference,
+    },
+    /// `label` names the loop to leave, the innermost one when None
+    Break {
+        label: Option<String>,
+        metadata: MetadataReference,
+    },
+    Continue {
+        label: Option<String>,
+        metadata: MetadataReference,
+    },
+    Return {
+        value: Option<Box<ASTnode>>,
+        metadata: MetadataReference,
+    },
     Assign {
         dest: Box<ASTnode>,
         expr: Box<ASTnode>,
         metadata: MetadataReference,
     },
+    /// dest op= expr, `dest` is evaluated once. `scale` is set by semantic analysis to the size of
+    /// the pointed to type when a pointer is moved with `+=` or `-=`, it is 1 otherwise
+    CompoundAssign {
+        dest: Box<ASTnode>,
+        operator: AssignOperator,
+        expr: Box<ASTnode>,
+        scale: usize,
+        metadata: MetadataReference,
+    },
+    /// `kind` is None when the type is to be inferred from `value`
+    /// `addressed` is set by semantic analysis when the address of the variable is taken,
+    /// forcing it onto the stack
     VariableDeclaration {
         name: String,
-        kind: Box<ASTnode>,
+        kind: Option<Box<ASTnode>>,
+        value: Option<Box<ASTnode>>,
+        addressed: bool,
+        metadata: MetadataReference,
+    },
+    /// let (a, b) = value; each binding is a VariableDeclaration without a value,
+    /// `_` skips an element
+    TupleDeclaration {
+        bindings: Vec<ASTnode>,
+        kind: Option<Box<ASTnode>>,
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    ConstDeclaration {
+        name: String,
+        public: bool,
+        kind: Option<Box<ASTnode>>,
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// a static without `value` is zero filled, an `external` one is a data label defined
+    /// outside of ansl and has no value
+    StaticDeclaration {
+        name: String,
+        public: bool,
+        kind: Option<Box<ASTnode>>,
+        mutable: bool,
+        external: bool,
+        value: Option<Box<ASTnode>>,
         metadata: MetadataReference,
     },
+    /// a `naked` function is declared with `asm fn`, its body is an Asm block emitted without a
+    /// prologue or epilogue. a `variadic` function takes more arguments after `params`, passed on
+    /// the stack. a `format` function is marked `@format`, its variadic arguments are checked
+    /// against its last parameter, a printf format string
     FunctionDefinition {
         name: String,
+        public: bool,
         params: Vec<ASTnode>,
+        return_type: Option<Box<ASTnode>>,
+        variadic: bool,
+        format: bool,
         body: Box<ASTnode>,
+        naked: bool,
+        metadata: MetadataReference,
+    },
+    /// extern fn name(params) -> T; a function written outside of ansl, called through its label
+    ExternFunction {
+        name: String,
+        public: bool,
+        params: Vec<ASTnode>,
+        return_type: Option<Box<ASTnode>>,
+        variadic: bool,
+        format: bool,
+        metadata: MetadataReference,
+    },
+    /// asm (in r1 = value, clobber r2) { "nsm line" ... }, the lines are passed through to the
+    /// output as they are
+    Asm {
+        #[allow(dead_code)]
+        lines: Vec<String>,
+        operands: Vec<ASTnode>,
+        #[allow(dead_code)]
+        metadata: MetadataReference,
+    },
+    /// `value` is None for clobbers
+    AsmOperand {
+        direction: AsmDirection,
+        register: String,
+        value: Option<Box<ASTnode>>,
         metadata: MetadataReference,
     },
 
@@ -59,118 +412,1730 @@ pub enum ASTnode {
     And(BinaryOP),
     Or(BinaryOP),
     Xor(BinaryOP),
+    /// && and ||, the right side is only evaluated when the left one does not decide the result
+    LogicalAnd(BinaryOP),
+    LogicalOr(BinaryOP),
+    ShiftLeft(BinaryOP),
+    /// logical, semantic analysis turns it into ArithmeticShiftRight when the value is signed
+    ShiftRight(BinaryOP),
+    ArithmeticShiftRight(BinaryOP),
     Not {
         value: Box<ASTnode>,
         metadata: MetadataReference,
     },
+    /// ~value
+    BitNot {
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    Negate {
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// &value
+    AddressOf {
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// &function, the label of a function, made from an AddressOf by semantic analysis
+    FunctionAddress {
+        name: String,
+        metadata: MetadataReference,
+    },
+    /// *value
+    Deref {
+        value: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// sizeof(T), alignof(T) and offsetof(T, a.b), replaced by NumberLiterals during semantic
+    /// analysis
+    SizeOf {
+        kind: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    AlignOf {
+        kind: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    OffsetOf {
+        kind: Box<ASTnode>,
+        fields: Vec<String>,
+        metadata: MetadataReference,
+    },
+    /// va_start(), a `*u64` to the stack slot of the first variadic argument
+    VaStart {
+        metadata: MetadataReference,
+    },
+    /// va_arg(list, T), reads the slot `list` points to as a `kind` and moves `list` to the next one
+    VaArg {
+        list: Box<ASTnode>,
+        kind: Box<ASTnode>,
+        metadata: MetadataReference,
+    },
+    /// value as kind
+    Cast {
+        value: Box<ASTnode>,
+        kind: Box<ASTnode>,
+        conversion: Conversion,
+        metadata: MetadataReference,
+    },
+    /// value[index], `checked` is set by semantic analysis when the index has to be bounds
+    /// checked at runtime
+    Index {
+        value: Box<ASTnode>,
+        index: Box<ASTnode>,
+        checked: bool,
+        metadata: MetadataReference,
+    },
+    /// pointer + offset * scale, pointer arithmetic rewritten by semantic analysis so that
+    /// `offset` counts elements, `scale` is the size of the element in bytes
+    PointerOffset {
+        pointer: Box<ASTnode>,
+        offset: Box<ASTnode>,
+        #[allow(dead_code)]
+        scale: usize,
+        #[allow(dead_code)]
+        negative: bool,
+        metadata: MetadataReference,
+    },
+
+    Equal(BinaryOP),
+    NotEqual(BinaryOP),
+    Lesser(BinaryOP),
+    Greater(BinaryOP),
+    LesserEq(BinaryOP),
+    GreaterEq(BinaryOP),
 }
 
-pub fn root_parse(mut token_stream: TokenStream) -> Result<ASTnode, CompileError> {
+impl ASTnode {
+    /// direct sub-expressions of an expression
+    pub fn children_mut(&mut self) -> Vec<&mut ASTnode> {
+        match self {
+            ASTnode::FunctionCall { params, .. } => params.iter_mut().collect(),
+            ASTnode::Call { callee, params, .. } => std::iter::once(callee.as_mut())
+                .chain(params.iter_mut())
+                .collect(),
+            ASTnode::ArrayLiteral { elements, .. } | ASTnode::TupleLiteral { elements, .. } => {
+                elements.iter_mut().collect()
+            }
+            ASTnode::StructLiteral { fields, .. } => fields.iter_mut().collect(),
+            ASTnode::FieldValue { value, .. }
+            | ASTnode::FieldAccess { value, .. }
+            | ASTnode::Not { value, .. }
+            | ASTnode::BitNot { value, .. }
+            | ASTnode::Negate { value, .. }
+            | ASTnode::AddressOf { value, .. }
+            | ASTnode::Deref { value, .. }
+            | ASTnode::ArrayRepeat { value, .. }
+            | ASTnode::Cast { value, .. }
+            | ASTnode::VaArg { list: value, .. } => vec![value.as_mut()],
+            ASTnode::AsmOperand { value, .. } => value.iter_mut().map(|v| v.as_mut()).collect(),
+            ASTnode::Index { value, index, .. } => vec![value.as_mut(), index.as_mut()],
+            ASTnode::PointerOffset {
+                pointer, offset, ..
+            } => vec![pointer.as_mut(), offset.as_mut()],
+            ASTnode::Range { start, end, .. } => vec![start.as_mut(), end.as_mut()],
+            ASTnode::Add(op)
+            | ASTnode::Sub(op)
+            | ASTnode::Mult(op)
+            | ASTnode::Div(op)
+            | ASTnode::Mod(op)
+            | ASTnode::And(op)
+            | ASTnode::Or(op)
+            | ASTnode::Xor(op)
+            | ASTnode::LogicalAnd(op)
+            | ASTnode::LogicalOr(op)
+            | ASTnode::ShiftLeft(op)
+            | ASTnode::ShiftRight(op)
+            | ASTnode::ArithmeticShiftRight(op)
+            | ASTnode::Equal(op)
+            | ASTnode::NotEqual(op)
+            | ASTnode::Lesser(op)
+            | ASTnode::Greater(op)
+            | ASTnode::LesserEq(op)
+            | ASTnode::GreaterEq(op) => vec![op.left.as_mut(), op.right.as_mut()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// where an expression or pattern starts in source, None for other nodes
+    pub fn metadata(&self) -> Option<&MetadataReference> {
+        match self {
+            ASTnode::Identifier { metadata, .. }
+            | ASTnode::NumberLiteral { metadata, .. }
+            | ASTnode::StringLiteral { metadata, .. }
+            | ASTnode::CharLiteral { metadata, .. }
+            | ASTnode::BoolLiteral { metadata, .. }
+            | ASTnode::ArrayLiteral { metadata, .. }
+            | ASTnode::ArrayRepeat { metadata, .. }
+            | ASTnode::TupleLiteral { metadata, .. }
+            | ASTnode::FunctionCall { metadata, .. }
+            | ASTnode::Call { metadata, .. }
+            | ASTnode::Path { metadata, .. }
+            | ASTnode::StructLiteral { metadata, .. }
+            | ASTnode::FieldAccess { metadata, .. }
+            | ASTnode::Not { metadata, .. }
+            | ASTnode::BitNot { metadata, .. }
+            | ASTnode::Negate { metadata, .. }
+            | ASTnode::AddressOf { metadata, .. }
+            | ASTnode::FunctionAddress { metadata, .. }
+            | ASTnode::Deref { metadata, .. }
+            | ASTnode::SizeOf { metadata, .. }
+            | ASTnode::AlignOf { metadata, .. }
+            | ASTnode::OffsetOf { metadata, .. }
+            | ASTnode::VaStart { metadata }
+            | ASTnode::VaArg { metadata, .. }
+            | ASTnode::Cast { metadata, .. }
+            | ASTnode::Index { metadata, .. }
+            | ASTnode::PointerOffset { metadata, .. }
+            | ASTnode::Range { metadata, .. }
+            | ASTnode::Wildcard { metadata } => Some(metadata),
+            ASTnode::Add(op)
+            | ASTnode::Sub(op)
+            | ASTnode::Mult(op)
+            | ASTnode::Div(op)
+            | ASTnode::Mod(op)
+            | ASTnode::And(op)
+            | ASTnode::Or(op)
+            | ASTnode::Xor(op)
+            | ASTnode::LogicalAnd(op)
+            | ASTnode::LogicalOr(op)
+            | ASTnode::ShiftLeft(op)
+            | ASTnode::ShiftRight(op)
+            | ASTnode::ArithmeticShiftRight(op)
+            | ASTnode::Equal(op)
+            | ASTnode::NotEqual(op)
+            | ASTnode::Lesser(op)
+            | ASTnode::Greater(op)
+            | ASTnode::LesserEq(op)
+            | ASTnode::GreaterEq(op) => Some(&op.metadata),
+            _ => None,
+        }
+    }
+}
+
+pub fn root_parse(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
     // let x : u8 = 1 + 2;
+    let mut types: Vec<ASTnode> = Vec::new();
     let mut functions: Vec<ASTnode> = Vec::new();
     let mut globals: Vec<ASTnode> = Vec::new();
+    let mut modules: Vec<ASTnode> = Vec::new();
+    let mut imports: Vec<ASTnode> = Vec::new();
+    let mut impls: Vec<ASTnode> = Vec::new();
     while !token_stream.eof() {
         let token = token_stream.next().unwrap();
-        let node: ASTnode = match token.kind {
-            TokenKind::KWfn => parse_function(&mut token_stream)?,
-            TokenKind::KWconst => parse_const(&mut token_stream)?,
-            TokenKind::KWstatic => parse_static(&mut token_stream)?,
+        let node = if token.is(TokenKind::KWpub) {
+            let token = token_stream.next().unwrap();
+            let mut node = parse_item(token_stream, token.clone())?;
+            match &mut node {
+                ASTnode::FunctionDefinition { public, .. }
+                | ASTnode::ExternFunction { public, .. }
+                | ASTnode::StructDeclaration { public, .. }
+                | ASTnode::EnumDeclaration { public, .. }
+                | ASTnode::ConstDeclaration { public, .. }
+                | ASTnode::StaticDeclaration { public, .. }
+                | ASTnode::Module { public, .. } => *public = true,
+                _ => {
+                    return Err(
+                        CompileError::new("`pub` can not be placed here".to_string())
+                            .attach_token(token)
+                            .fmt_metadata(&token_stream.source),
+                    )
+                }
+            }
+            node
+        } else {
+            parse_item(token_stream, token)?
+        };
+        match node {
+            ASTnode::FunctionDefinition { .. } | ASTnode::ExternFunction { .. } => {
+                functions.push(node)
+            }
+            ASTnode::StructDeclaration { .. } | ASTnode::EnumDeclaration { .. } => types.push(node),
+            ASTnode::Module { .. } => modules.push(node),
+            ASTnode::Use { .. } => imports.push(node),
+            ASTnode::Impl { .. } => impls.push(node),
+            _ => globals.push(node),
+        }
+    }
+    Ok(ASTnode::Root {
+        types,
+        globals,
+        functions,
+        modules,
+        imports,
+        impls,
+    })
+}
+/// an item of the root namespace starting with `token`
+fn parse_item(token_stream: &mut TokenStream, token: Token) -> Result<ASTnode, CompileError> {
+    Ok(match token.kind {
+        TokenKind::KWfn => parse_function(token_stream, false)?,
+        TokenKind::KWasm => {
+            token_stream.expect(TokenKind::KWfn, "expected `fn` after `asm`")?;
+            parse_function(token_stream, true)?
+        }
+        TokenKind::KWconst => parse_const(token_stream)?,
+        TokenKind::KWstatic => parse_static(token_stream, false)?,
+        TokenKind::KWextern => parse_extern(token_stream)?,
+        TokenKind::At => parse_attribute(token_stream)?,
+        TokenKind::KWstruct => parse_struct(token_stream, false)?,
+        TokenKind::KWenum => parse_enum(token_stream)?,
+        TokenKind::KWmod => parse_module(token_stream)?,
+        TokenKind::KWuse => parse_use(token_stream)?,
+        TokenKind::KWimpl => parse_impl(token_stream, token)?,
+        // `packed` is contextual, only a keyword directly before `struct`
+        TokenKind::Identifer
+            if token.lexeme == "packed" && token_stream.peek_is(TokenKind::KWstruct) =>
+        {
+            token_stream.next();
+            parse_struct(token_stream, true)?
+        }
+        _ => {
+            return Err(CompileError::new(format!(
+                "statement `{}` not allowed in root namespace",
+                token.lexeme
+            ))
+            .attach_token(token)
+            .fmt_metadata(&token_stream.source))
+        }
+    })
+}
+/// mod name; parses name.ansl next to the current file, or else from the system library
+fn parse_module(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let name = token_stream.expect(TokenKind::Identifer, "expected module name")?;
+    token_stream.expect(TokenKind::SemiColon, "expected `;` after module name")?;
+    let file = name.lexeme.clone() + SOURCE_FILE_EXTENSION;
+    let local = Path::new(name.metadata.file_name())
+        .with_file_name(&file)
+        .to_string_lossy()
+        .into_owned();
+    let path = if Path::new(&local).exists() {
+        local
+    } else {
+        SYSTEM_LIB_ROOT.to_owned() + &file
+    };
+    if token_stream.modules.contains(&path) {
+        return Err(
+            CompileError::new(format!("module `{}` contains itself", name.lexeme))
+                .attach_token(name)
+                .fmt_metadata(&token_stream.source),
+        );
+    }
+    let mut module_stream = TokenStream::new();
+    module_stream.modules = token_stream.modules.clone();
+    module_stream.modules.push(path.clone());
+    module_stream.tokenize_source_tree(&path).map_err(|e| {
+        e.attach_token(name.clone())
+            .fmt_metadata(&token_stream.source)
+    })?;
+    let root = root_parse(&mut module_stream)?;
+    token_stream.source.merge(module_stream.source);
+    Ok(ASTnode::Module {
+        name: name.lexeme,
+        public: false,
+        root: Box::new(root),
+        metadata: name.metadata,
+    })
+}
+/// use a::b::item [as alias];
+fn parse_use(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let first = token_stream.expect(TokenKind::Identifer, "expected path after `use`")?;
+    let mut path = vec![first.lexeme];
+    while token_stream.peek_is(TokenKind::DoubleColon) {
+        token_stream.next();
+        let segment = token_stream.expect(TokenKind::Identifer, "expected name after `::`")?;
+        path.push(segment.lexeme);
+    }
+    let alias = if token_stream.peek_is(TokenKind::KWas) {
+        token_stream.next();
+        Some(
+            token_stream
+                .expect(TokenKind::Identifer, "expected name after `as`")?
+                .lexeme,
+        )
+    } else {
+        None
+    };
+    token_stream.expect(TokenKind::SemiColon, "expected `;` after use")?;
+    Ok(ASTnode::Use {
+        path,
+        alias,
+        metadata: first.metadata,
+    })
+}
+/// impl Type { fn ... const ... }, its items are public wherever the type is
+fn parse_impl(token_stream: &mut TokenStream, token: Token) -> Result<ASTnode, CompileError> {
+    let kind = parse_type(token_stream)?;
+    token_stream.expect(TokenKind::OpenCurly, "expected `{` after impl type")?;
+    let mut items: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        let token = token_stream.next().unwrap();
+        let item = match token.kind {
+            TokenKind::KWfn | TokenKind::KWasm | TokenKind::KWconst | TokenKind::At => {
+                parse_item(token_stream, token.clone())?
+            }
             _ => {
                 return Err(CompileError::new(format!(
-                    "statement `{}` not allowed in root namespace",
+                    "statement `{}` not allowed in an impl, only functions and consts",
                     token.lexeme
                 ))
                 .attach_token(token)
                 .fmt_metadata(&token_stream.source))
             }
         };
-        match node {
-            ASTnode::FunctionDefinition { .. } => functions.push(node),
-            _ => globals.push(node),
+        if matches!(item, ASTnode::ExternFunction { .. }) {
+            return Err(
+                CompileError::new("an impl can not hold extern functions".to_string())
+                    .attach_token(token)
+                    .fmt_metadata(&token_stream.source),
+            );
+        }
+        items.push(item);
+    }
+    token_stream.next();
+    Ok(ASTnode::Impl {
+        kind: Box::new(kind),
+        items,
+        metadata: token.metadata,
+    })
+}
+/// enum Name[: type] { Variant [= value], Variant(type, ...), ... }
+fn parse_enum(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let name = token_stream.expect(TokenKind::Identifer, "expected enum name")?;
+    let kind = if token_stream.peek_is(TokenKind::Colon) {
+        token_stream.next();
+        Some(Box::new(parse_type(token_stream)?))
+    } else {
+        None
+    };
+    token_stream.expect(TokenKind::OpenCurly, "expected `{` after enum name")?;
+    let mut variants: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        let variant = token_stream.expect(TokenKind::Identifer, "expected variant name")?;
+        let mut fields: Vec<ASTnode> = Vec::new();
+        if token_stream.peek_is(TokenKind::OpenParenth) {
+            token_stream.next();
+            while !token_stream.peek_is(TokenKind::ClosedParenth) {
+                let metadata = token_stream.peek().unwrap().metadata.clone();
+                let kind = parse_type(token_stream)?;
+                fields.push(ASTnode::Field {
+                    name: fields.len().to_string(),
+                    metadata,
+                    kind: Box::new(kind),
+                    offset: 0,
+                });
+                if !token_stream.peek_is(TokenKind::ClosedParenth) {
+                    token_stream.expect(TokenKind::Comma, "expected `,` or `)` after type")?;
+                }
+            }
+            token_stream.next();
+        }
+        let value = if token_stream.peek_is(TokenKind::Assign) {
+            token_stream.next();
+            Some(Box::new(parse_pattern_literal(token_stream)?))
+        } else {
+            None
+        };
+        variants.push(ASTnode::EnumVariant {
+            name: variant.lexeme,
+            value,
+            fields,
+            discriminant: 0,
+            metadata: variant.metadata,
+        });
+        if !token_stream.peek_is(TokenKind::ClosedCurly) {
+            token_stream.expect(TokenKind::Comma, "expected `,` or `}` after variant")?;
         }
     }
-    Ok(ASTnode::Root { globals, functions })
+    token_stream.next();
+    Ok(ASTnode::EnumDeclaration {
+        name: name.lexeme,
+        public: false,
+        kind,
+        variants,
+        size: 0,
+        align: 0,
+        metadata: name.metadata,
+    })
 }
-fn parse_function(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
-    todo!("function")
+/// [packed] struct Name { field: type, ... }
+fn parse_struct(token_stream: &mut TokenStream, packed: bool) -> Result<ASTnode, CompileError> {
+    let name = token_stream.expect(TokenKind::Identifer, "expected struct name")?;
+    token_stream.expect(TokenKind::OpenCurly, "expected `{` after struct name")?;
+    let mut fields: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        let field = token_stream.expect(TokenKind::Identifer, "expected field name")?;
+        token_stream.expect(TokenKind::Colon, "expected colon after field name")?;
+        let kind = parse_type(token_stream)?;
+        fields.push(ASTnode::Field {
+            name: field.lexeme,
+            kind: Box::new(kind),
+            offset: 0,
+            metadata: field.metadata,
+        });
+        if !token_stream.peek_is(TokenKind::ClosedCurly) {
+            token_stream.expect(TokenKind::Comma, "expected `,` or `}` after field")?;
+        }
+    }
+    token_stream.next();
+    Ok(ASTnode::StructDeclaration {
+        name: name.lexeme,
+        public: false,
+        fields,
+        packed,
+        size: 0,
+        align: 0,
+        metadata: name.metadata,
+    })
 }
-fn parse_const(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
-    enum ConstState {
-        Initial,
-        VariableName(Token),
-        Colon(Token),
-        Type(ASTnode),
-    }
-    let mut state = ConstState::Initial;
-    while !token_stream.peek_is(TokenKind::SemiColon) {
-        let token = token_stream.next().unwrap();
-        println!("{}", token);
-        state = match state {
-            ConstState::Initial => {
-                if token.is(TokenKind::Identifer) {
-                    ConstState::VariableName(token)
-                } else {
-                    return Err(CompileError::new("expected variable name".to_string())
-                        .attach_token(token)
-                        .fmt_metadata(&token_stream.source));
+/// fn name(a: u8, b: u16) -> u32 { ... }
+fn parse_function(token_stream: &mut TokenStream, naked: bool) -> Result<ASTnode, CompileError> {
+    let (name, params, return_type, variadic) = parse_signature(token_stream)?;
+    let body = if naked {
+        ASTnode::Asm {
+            lines: parse_asm_lines(token_stream)?,
+            operands: Vec::new(),
+            metadata: name.metadata.clone(),
+        }
+    } else {
+        parse_block(token_stream)?
+    };
+    Ok(ASTnode::FunctionDefinition {
+        name: name.lexeme,
+        public: false,
+        params,
+        return_type,
+        variadic,
+        format: false,
+        body: Box::new(body),
+        naked,
+        metadata: name.metadata,
+    })
+}
+/// @format before a function, `fn`, `asm fn` or `extern fn`, which can be `pub`
+fn parse_attribute(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let attribute = token_stream.expect(TokenKind::Identifer, "expected attribute name")?;
+    if attribute.lexeme != "format" {
+        return Err(
+            CompileError::new(format!("unknown attribute `{}`", attribute.lexeme))
+                .attach_token(attribute)
+                .fmt_metadata(&token_stream.source),
+        );
+    }
+    let is_public = token_stream.peek_is(TokenKind::KWpub);
+    if is_public {
+        token_stream.next();
+    }
+    let token = token_stream.next().unwrap();
+    let mut node = match token.kind {
+        TokenKind::KWfn => parse_function(token_stream, false)?,
+        TokenKind::KWasm => {
+            token_stream.expect(TokenKind::KWfn, "expected `fn` after `asm`")?;
+            parse_function(token_stream, true)?
+        }
+        TokenKind::KWextern if token_stream.peek_is(TokenKind::KWfn) => parse_extern(token_stream)?,
+        _ => {
+            return Err(
+                CompileError::new("`@format` can only be placed on a function".to_string())
+                    .attach_token(token)
+                    .fmt_metadata(&token_stream.source),
+            )
+        }
+    };
+    if let ASTnode::FunctionDefinition { format, public, .. }
+    | ASTnode::ExternFunction { format, public, .. } = &mut node
+    {
+        *format = true;
+        *public = is_public;
+    }
+    Ok(node)
+}
+/// extern fn name(params) -> T; or extern static [mut] NAME: T;
+fn parse_extern(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let token = token_stream.next().unwrap();
+    match token.kind {
+        TokenKind::KWfn => {
+            let (name, params, return_type, variadic) = parse_signature(token_stream)?;
+            token_stream.expect(
+                TokenKind::SemiColon,
+                "expected `;`, an extern function has no body",
+            )?;
+            Ok(ASTnode::ExternFunction {
+                name: name.lexeme,
+                public: false,
+                params,
+                return_type,
+                variadic,
+                format: false,
+                metadata: name.metadata,
+            })
+        }
+        TokenKind::KWstatic => parse_static(token_stream, true),
+        _ => Err(
+            CompileError::new("expected `fn` or `static` after `extern`".to_string())
+                .attach_token(token)
+                .fmt_metadata(&token_stream.source),
+        ),
+    }
+}
+/// name(params) -> T, and whether the last parameter is `...`
+#[allow(clippy::type_complexity)]
+fn parse_signature(
+    token_stream: &mut TokenStream,
+) -> Result<(Token, Vec<ASTnode>, Option<Box<ASTnode>>, bool), CompileError> {
+    let name = token_stream.expect(TokenKind::Identifer, "expected function name")?;
+    token_stream.expect(TokenKind::OpenParenth, "expected `(` after function name")?;
+    let mut params: Vec<ASTnode> = Vec::new();
+    let mut variadic = false;
+    while !token_stream.peek_is(TokenKind::ClosedParenth) {
+        if parse_ellipsis(token_stream)? {
+            variadic = true;
+            break;
+        }
+        let param = token_stream.expect(TokenKind::Identifer, "expected parameter name")?;
+        token_stream.expect(TokenKind::Colon, "expected colon after parameter name")?;
+        let kind = parse_type(token_stream)?;
+        params.push(ASTnode::Variable {
+            name: param.lexeme,
+            kind: Box::new(kind),
+            addressed: false,
+            metadata: param.metadata,
+        });
+        if !token_stream.peek_is(TokenKind::ClosedParenth) {
+            token_stream.expect(TokenKind::Comma, "expected `,` or `)` after parameter")?;
+        }
+    }
+    token_stream.expect(TokenKind::ClosedParenth, "expected `)`")?;
+    let return_type = if token_stream.peek_is(TokenKind::Arrow) {
+        token_stream.next();
+        Some(Box::new(parse_type(token_stream)?))
+    } else {
+        None
+    };
+    Ok((name, params, return_type, variadic))
+}
+/// `...` as the last parameter, it is lexed as three dots
+fn parse_ellipsis(token_stream: &mut TokenStream) -> Result<bool, CompileError> {
+    let is_ellipsis = (0..3).all(|n| {
+        token_stream
+            .peek_nth(n)
+            .is_some_and(|t| t.is(TokenKind::Dot))
+    });
+    if is_ellipsis {
+        for _ in 0..3 {
+            token_stream.next();
+        }
+        if !token_stream.peek_is(TokenKind::ClosedParenth) {
+            token_stream.expect(
+                TokenKind::ClosedParenth,
+                "`...` has to be the last parameter",
+            )?;
+        }
+    }
+    Ok(is_ellipsis)
+}
+fn parse_type(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let token = token_stream.next().unwrap();
+    match token.kind {
+        TokenKind::Primitive(kind) => Ok(ASTnode::Type {
+            name: kind,
+            metadata: token.metadata,
+        }),
+        // user defined, resolved during semantic analysis. a path through modules is kept as
+        // written, `a::B`, until module resolution
+        TokenKind::Identifer => {
+            let mut name = token.lexeme;
+            while token_stream.peek_is(TokenKind::DoubleColon) {
+                token_stream.next();
+                let segment =
+                    token_stream.expect(TokenKind::Identifer, "expected name after `::`")?;
+                name = name + "::" + &segment.lexeme;
+            }
+            Ok(ASTnode::Type {
+                name,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::Star | TokenKind::And => Ok(ASTnode::PointerType {
+            kind: Box::new(parse_type(token_stream)?),
+            metadata: token.metadata,
+        }),
+        TokenKind::KWfn => {
+            token_stream.expect(TokenKind::OpenParenth, "expected `(` after `fn`")?;
+            let mut params: Vec<ASTnode> = Vec::new();
+            let mut variadic = false;
+            while !token_stream.peek_is(TokenKind::ClosedParenth) {
+                if parse_ellipsis(token_stream)? {
+                    variadic = true;
+                    break;
+                }
+                params.push(parse_type(token_stream)?);
+                if !token_stream.peek_is(TokenKind::ClosedParenth) {
+                    token_stream.expect(
+                        TokenKind::Comma,
+                        "expected `,` or `)` in function pointer type",
+                    )?;
                 }
             }
-            ConstState::VariableName(variable) => {
-                if token.is(TokenKind::Colon) {
-                    ConstState::Colon(variable)
+            token_stream.next();
+            let return_type = if token_stream.peek_is(TokenKind::Arrow) {
+                token_stream.next();
+                Some(Box::new(parse_type(token_stream)?))
+            } else {
+                None
+            };
+            Ok(ASTnode::FunctionType {
+                params,
+                return_type,
+                variadic,
+                metadata: token.metadata,
+            })
+        }
+        // (A, B, ...), a single type in parentheses without a trailing comma is just that type
+        TokenKind::OpenParenth => {
+            let mut elements: Vec<ASTnode> = Vec::new();
+            while !token_stream.peek_is(TokenKind::ClosedParenth) {
+                elements.push(parse_type(token_stream)?);
+                if token_stream.peek_is(TokenKind::ClosedParenth) {
+                    if elements.len() == 1 {
+                        token_stream.next();
+                        return Ok(elements.pop().unwrap());
+                    }
                 } else {
-                    return Err(CompileError::new("expected colon".to_string())
-                        .attach_token(token)
-                        .fmt_metadata(&token_stream.source));
+                    token_stream.expect(TokenKind::Comma, "expected `,` or `)` in tuple type")?;
                 }
             }
-            ConstState::Colon(variable) => match token.kind {
-                TokenKind::Primitive(kind) => ConstState::Type(ASTnode::VariableDeclaration {
-                    name: variable.lexeme,
-                    kind: Box::new(ASTnode::Type {
-                        name: kind,
-                        metadata: token.metadata,
-                    }),
-                    metadata: variable.metadata,
-                }),
+            token_stream.next();
+            Ok(ASTnode::TupleType {
+                elements,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::OpenBracket => {
+            let kind = parse_type(token_stream)?;
+            token_stream.expect(
+                TokenKind::SemiColon,
+                "expected `;` after array element type",
+            )?;
+            let length = parse_length(token_stream)?;
+            token_stream.expect(TokenKind::ClosedBracket, "expected `]`")?;
+            Ok(ASTnode::ArrayType {
+                kind: Box::new(kind),
+                length,
+                metadata: token.metadata,
+            })
+        }
+        _ => Err(CompileError::new("expected type".to_string())
+            .attach_token(token)
+            .fmt_metadata(&token_stream.source)),
+    }
+}
+/// { statement; ... }
+fn parse_block(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    token_stream.expect(TokenKind::OpenCurly, "expected `{`")?;
+    let mut nodes: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        if token_stream.eof() {
+            return Err(
+                CompileError::new("unclosed block, expected `}`".to_string())
+                    .attach_token(token_stream.next().unwrap())
+                    .fmt_metadata(&token_stream.source),
+            );
+        }
+        nodes.push(parse_statement(token_stream)?);
+    }
+    token_stream.expect(TokenKind::ClosedCurly, "expected `}`")?;
+    Ok(ASTnode::Block { nodes })
+}
+fn parse_statement(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let token = token_stream.peek().unwrap().clone();
+    match token.kind {
+        TokenKind::KWlet => {
+            token_stream.next();
+            if token_stream.peek_is(TokenKind::OpenParenth) {
+                parse_tuple_declaration(token_stream)
+            } else {
+                parse_variable_declaration(token_stream)
+            }
+        }
+        TokenKind::KWconst => {
+            token_stream.next();
+            parse_const(token_stream)
+        }
+        TokenKind::KWstatic => {
+            token_stream.next();
+            parse_static(token_stream, false)
+        }
+        TokenKind::OpenCurly => parse_block(token_stream),
+        TokenKind::KWif => {
+            token_stream.next();
+            parse_if(token_stream, token)
+        }
+        TokenKind::KWwhile => {
+            token_stream.next();
+            let condition = parse_expr(token_stream)?;
+            let body = parse_block(token_stream)?;
+            Ok(ASTnode::While {
+                condition: Box::new(condition),
+                body: Box::new(body),
+                label: None,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::KWasm => {
+            token_stream.next();
+            parse_asm(token_stream, token)
+        }
+        TokenKind::KWloop => {
+            token_stream.next();
+            let body = parse_block(token_stream)?;
+            Ok(ASTnode::Loop {
+                body: Box::new(body),
+                label: None,
+                metadata: token.metadata,
+            })
+        }
+        // 'label: loop
+        TokenKind::Label => {
+            token_stream.next();
+            token_stream.expect(TokenKind::Colon, "expected `:` after label")?;
+            let mut statement = parse_statement(token_stream)?;
+            match &mut statement {
+                ASTnode::While { label, .. }
+                | ASTnode::For { label, .. }
+                | ASTnode::Loop { label, .. } => *label = Some(token.lexeme),
+                _ => {
+                    return Err(CompileError::new(
+                        "labels can only be placed on `loop`, `while` and `for`".to_string(),
+                    )
+                    .attach_token(token)
+                    .fmt_metadata(&token_stream.source))
+                }
+            }
+            Ok(statement)
+        }
+        TokenKind::KWfor => {
+            token_stream.next();
+            parse_for(token_stream, token)
+        }
+        TokenKind::KWmatch => {
+            token_stream.next();
+            parse_match(token_stream, token)
+        }
+        TokenKind::KWbreak => {
+            token_stream.next();
+            let label = parse_label(token_stream);
+            token_stream.expect(TokenKind::SemiColon, "expected `;` after `break`")?;
+            Ok(ASTnode::Break {
+                label,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::KWcontinue => {
+            token_stream.next();
+            let label = parse_label(token_stream);
+            token_stream.expect(TokenKind::SemiColon, "expected `;` after `continue`")?;
+            Ok(ASTnode::Continue {
+                label,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::KWreturn => {
+            token_stream.next();
+            let value = if token_stream.peek_is(TokenKind::SemiColon) {
+                None
+            } else {
+                Some(Box::new(parse_expr(token_stream)?))
+            };
+            token_stream.expect(TokenKind::SemiColon, "expected `;` after return value")?;
+            Ok(ASTnode::Return {
+                value,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::KWelse => Err(CompileError::new(
+            "`else` without a preceding `if`, an `else` must directly follow the closing `}` of an `if`"
+                .to_string(),
+        )
+        .attach_token(token)
+        .fmt_metadata(&token_stream.source)),
+        _ => {
+            let expr = parse_expr(token_stream)?;
+            if token_stream.peek_is(TokenKind::Assign) {
+                let assign = token_stream.next().unwrap();
+                let value = parse_expr(token_stream)?;
+                token_stream.expect(TokenKind::SemiColon, "expected `;` after assignment")?;
+                return Ok(ASTnode::Assign {
+                    dest: Box::new(expr),
+                    expr: Box::new(value),
+                    metadata: assign.metadata,
+                });
+            }
+            if let Some(operator) = token_stream.peek().and_then(|t| assign_operator(&t.kind)) {
+                let assign = token_stream.next().unwrap();
+                let value = parse_expr(token_stream)?;
+                token_stream.expect(TokenKind::SemiColon, "expected `;` after assignment")?;
+                return Ok(ASTnode::CompoundAssign {
+                    dest: Box::new(expr),
+                    operator,
+                    expr: Box::new(value),
+                    scale: 1,
+                    metadata: assign.metadata,
+                });
+            }
+            token_stream.expect(TokenKind::SemiColon, "expected `;` after expression")?;
+            Ok(expr)
+        }
+    }
+}
+/// asm (in r1 = a, out r2 = b, inout r3 = c, clobber r4) { "line" ... }, the operands are
+/// optional
+fn parse_asm(token_stream: &mut TokenStream, keyword: Token) -> Result<ASTnode, CompileError> {
+    let mut operands: Vec<ASTnode> = Vec::new();
+    if token_stream.peek_is(TokenKind::OpenParenth) {
+        token_stream.next();
+        while !token_stream.peek_is(TokenKind::ClosedParenth) {
+            let token = token_stream.next().unwrap();
+            let direction = match (&token.kind, token.lexeme.as_str()) {
+                (TokenKind::KWin, _) => AsmDirection::In,
+                (TokenKind::Identifer, "out") => AsmDirection::Out,
+                (TokenKind::Identifer, "inout") => AsmDirection::InOut,
+                (TokenKind::Identifer, "clobber") => AsmDirection::Clobber,
                 _ => {
-                    return Err(CompileError::new("expected primitive type".to_string())
-                        .attach_token(token)
-                        .fmt_metadata(&token_stream.source))
+                    return Err(CompileError::new(
+                        "expected `in`, `out`, `inout` or `clobber`".to_string(),
+                    )
+                    .attach_token(token)
+                    .fmt_metadata(&token_stream.source))
+                }
+            };
+            let register = token_stream.expect(TokenKind::Identifer, "expected register")?;
+            let value = if direction == AsmDirection::Clobber {
+                None
+            } else {
+                token_stream.expect(TokenKind::Assign, "expected `=` after register")?;
+                Some(Box::new(parse_expr(token_stream)?))
+            };
+            operands.push(ASTnode::AsmOperand {
+                direction,
+                register: register.lexeme,
+                value,
+                metadata: register.metadata,
+            });
+            if !token_stream.peek_is(TokenKind::ClosedParenth) {
+                token_stream.expect(TokenKind::Comma, "expected `,` or `)` after operand")?;
+            }
+        }
+        token_stream.next();
+    }
+    Ok(ASTnode::Asm {
+        lines: parse_asm_lines(token_stream)?,
+        operands,
+        metadata: keyword.metadata,
+    })
+}
+/// { "line" "line" ... }, one string per NSM line
+fn parse_asm_lines(token_stream: &mut TokenStream) -> Result<Vec<String>, CompileError> {
+    token_stream.expect(TokenKind::OpenCurly, "expected `{`")?;
+    let mut lines: Vec<String> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        let line = token_stream.expect(
+            TokenKind::StringLiteral,
+            "expected a string, asm lines are written as strings",
+        )?;
+        lines.push(line.lexeme);
+    }
+    token_stream.next();
+    Ok(lines)
+}
+/// optional label after `break` and `continue`
+fn parse_label(token_stream: &mut TokenStream) -> Option<String> {
+    if token_stream.peek_is(TokenKind::Label) {
+        token_stream.next().map(|t| t.lexeme)
+    } else {
+        None
+    }
+}
+/// if cond { ... } [else if cond { ... }] [else { ... }]
+fn parse_if(token_stream: &mut TokenStream, keyword: Token) -> Result<ASTnode, CompileError> {
+    let condition = parse_expr(token_stream)?;
+    let body = parse_block(token_stream)?;
+    let else_body = if token_stream.peek_is(TokenKind::KWelse) {
+        token_stream.next();
+        if token_stream.peek_is(TokenKind::KWif) {
+            let token = token_stream.next().unwrap();
+            Some(Box::new(parse_if(token_stream, token)?))
+        } else {
+            Some(Box::new(parse_block(token_stream)?))
+        }
+    } else {
+        None
+    };
+    Ok(ASTnode::If {
+        condition: Box::new(condition),
+        body: Box::new(body),
+        else_body,
+        metadata: keyword.metadata,
+    })
+}
+/// for name in expr { ... }
+/// for name in [rev] a..b [step n] { ... }
+/// for name in [rev] a..=b [step n] { ... }
+fn parse_for(token_stream: &mut TokenStream, keyword: Token) -> Result<ASTnode, CompileError> {
+    let variable = token_stream.expect(TokenKind::Identifer, "expected loop variable name")?;
+    token_stream.expect(TokenKind::KWin, "expected `in` after loop variable")?;
+    // `rev` and `step` are contextual, they stay usable as variable names
+    let reverse = is_contextual(token_stream, "rev")
+        && !token_stream
+            .peek_nth(1)
+            .is_some_and(|t| t.is(TokenKind::Dot) || t.is(TokenKind::OpenCurly));
+    if reverse {
+        token_stream.next();
+    }
+    let start = parse_expr(token_stream)?;
+    if !token_stream.peek_is(TokenKind::Dot) {
+        return Err(CompileError::new(
+            "`for` iterates over a range, `start..end` or `start..=end`".to_string(),
+        )
+        .attach_metadata(start.metadata().unwrap_or(&variable.metadata).clone())
+        .fmt_metadata(&token_stream.source));
+    }
+    let iterable = parse_range(token_stream, start)?;
+    let step = if is_contextual(token_stream, "step") {
+        token_stream.next();
+        Some(Box::new(parse_expr(token_stream)?))
+    } else {
+        None
+    };
+    let body = parse_block(token_stream)?;
+    Ok(ASTnode::For {
+        variable: variable.lexeme,
+        iterable: Box::new(iterable),
+        reverse,
+        step,
+        body: Box::new(body),
+        label: None,
+        metadata: keyword.metadata,
+    })
+}
+/// match expr { pattern => expr, pattern => { ... } }
+fn parse_match(token_stream: &mut TokenStream, keyword: Token) -> Result<ASTnode, CompileError> {
+    let value = parse_expr(token_stream)?;
+    token_stream.expect(TokenKind::OpenCurly, "expected `{` after match value")?;
+    let mut arms: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        let pattern = parse_pattern(token_stream)?;
+        let arrow = token_stream.expect(TokenKind::FatArrow, "expected `=>` after pattern")?;
+        let body = if token_stream.peek_is(TokenKind::OpenCurly) {
+            let block = parse_block(token_stream)?;
+            if token_stream.peek_is(TokenKind::Comma) {
+                token_stream.next();
+            }
+            block
+        } else {
+            let expr = parse_expr(token_stream)?;
+            if !token_stream.peek_is(TokenKind::ClosedCurly) {
+                token_stream.expect(TokenKind::Comma, "expected `,` after match arm")?;
+            }
+            expr
+        };
+        arms.push(ASTnode::MatchArm {
+            pattern: Box::new(pattern),
+            body: Box::new(body),
+            metadata: arrow.metadata,
+        });
+    }
+    token_stream.next();
+    Ok(ASTnode::Match {
+        value: Box::new(value),
+        arms,
+        jump_table: false,
+        metadata: keyword.metadata,
+    })
+}
+/// `_`, an integer or character literal, or a range of them
+fn parse_pattern(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    if is_contextual(token_stream, "_") {
+        let token = token_stream.next().unwrap();
+        return Ok(ASTnode::Wildcard {
+            metadata: token.metadata,
+        });
+    }
+    if token_stream.peek_is(TokenKind::Identifer) {
+        return parse_variant_pattern(token_stream);
+    }
+    let start = parse_pattern_literal(token_stream)?;
+    if !token_stream.peek_is(TokenKind::Dot) {
+        return Ok(start);
+    }
+    let dot = token_stream.expect(TokenKind::Dot, "expected `..`")?;
+    token_stream.expect(TokenKind::Dot, "expected `..`")?;
+    let inclusive = token_stream.peek_is(TokenKind::Assign);
+    if inclusive {
+        token_stream.next();
+    }
+    let end = parse_pattern_literal(token_stream)?;
+    Ok(ASTnode::Range {
+        start: Box::new(start),
+        end: Box::new(end),
+        inclusive,
+        metadata: dot.metadata,
+    })
+}
+/// Enum::Variant or Enum::Variant(binding, _, ...)
+fn parse_variant_pattern(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let first = token_stream.expect(TokenKind::Identifer, "expected pattern")?;
+    let mut segments = vec![first.lexeme];
+    while token_stream.peek_is(TokenKind::DoubleColon) {
+        token_stream.next();
+        let segment = token_stream.expect(TokenKind::Identifer, "expected name after `::`")?;
+        segments.push(segment.lexeme);
+    }
+    let mut bindings: Vec<ASTnode> = Vec::new();
+    if token_stream.peek_is(TokenKind::OpenParenth) {
+        token_stream.next();
+        while !token_stream.peek_is(TokenKind::ClosedParenth) {
+            let binding = token_stream.expect(TokenKind::Identifer, "expected binding name")?;
+            bindings.push(if binding.lexeme == "_" {
+                ASTnode::Wildcard {
+                    metadata: binding.metadata,
+                }
+            } else {
+                ASTnode::Identifier {
+                    name: binding.lexeme,
+                    metadata: binding.metadata,
                 }
-            },
-            ConstState::Type(left) => {
-                if token.is(TokenKind::Assign) {
-                    let expr = parse_expr(token_stream)?;
-                    let return_node = ASTnode::Assign {
-                        dest: Box::new(left),
-                        expr: Box::new(expr),
+            });
+            if !token_stream.peek_is(TokenKind::ClosedParenth) {
+                token_stream.expect(TokenKind::Comma, "expected `,` or `)` after binding")?;
+            }
+        }
+        token_stream.next();
+    }
+    Ok(ASTnode::VariantPattern {
+        segments,
+        bindings,
+        metadata: first.metadata,
+    })
+}
+fn parse_pattern_literal(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    match token_stream.peek().map(|t| t.kind.clone()) {
+        Some(TokenKind::NumberLiteral)
+        | Some(TokenKind::CharLiteral)
+        | Some(TokenKind::KWtrue)
+        | Some(TokenKind::KWfalse) => parse_primary(token_stream),
+        Some(TokenKind::Dash) => {
+            let token = token_stream.next().unwrap();
+            let value = parse_pattern_literal(token_stream)?;
+            Ok(ASTnode::Negate {
+                value: Box::new(value),
+                metadata: token.metadata,
+            })
+        }
+        _ => {
+            let token = token_stream.next().unwrap();
+            Err(CompileError::new("expected pattern".to_string())
+                .attach_token(token)
+                .fmt_metadata(&token_stream.source))
+        }
+    }
+}
+/// the `..end` or `..=end` following an already parsed `start`
+fn parse_range(token_stream: &mut TokenStream, start: ASTnode) -> Result<ASTnode, CompileError> {
+    let dot = token_stream.expect(TokenKind::Dot, "expected `..`")?;
+    token_stream.expect(TokenKind::Dot, "expected `..`")?;
+    let inclusive = token_stream.peek_is(TokenKind::Assign);
+    if inclusive {
+        token_stream.next();
+    }
+    let end = parse_expr(token_stream)?;
+    Ok(ASTnode::Range {
+        start: Box::new(start),
+        end: Box::new(end),
+        inclusive,
+        metadata: dot.metadata,
+    })
+}
+/// true if the next token is the identifier `word`
+fn is_contextual(token_stream: &TokenStream, word: &str) -> bool {
+    token_stream
+        .peek()
+        .is_some_and(|t| t.is(TokenKind::Identifer) && t.lexeme == word)
+}
+/// const name [: type] = expr;
+/// read-only, always initialized
+fn parse_const(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let (name, kind, value) = parse_declaration(token_stream)?;
+    let value = match value {
+        Some(v) => v,
+        None => {
+            return Err(CompileError::new("const must be initialized".to_string())
+                .attach_token(name)
+                .fmt_metadata(&token_stream.source))
+        }
+    };
+    Ok(ASTnode::ConstDeclaration {
+        name: name.lexeme,
+        public: false,
+        kind: kind.map(Box::new),
+        value: Box::new(value),
+        metadata: name.metadata,
+    })
+}
+/// static [mut] name [: type] [= expr];
+/// global storage, zero filled when no initializer is given
+fn parse_static(token_stream: &mut TokenStream, external: bool) -> Result<ASTnode, CompileError> {
+    let mutable = token_stream.peek_is(TokenKind::KWmut);
+    if mutable {
+        token_stream.next();
+    }
+    let (name, kind, value) = parse_declaration(token_stream)?;
+    if external && (kind.is_none() || value.is_some()) {
+        return Err(CompileError::new(
+            "an extern static needs a type and cannot have a value".to_string(),
+        )
+        .attach_token(name)
+        .fmt_metadata(&token_stream.source));
+    }
+    Ok(ASTnode::StaticDeclaration {
+        name: name.lexeme,
+        public: false,
+        kind: kind.map(Box::new),
+        mutable,
+        external,
+        value: value.map(Box::new),
+        metadata: name.metadata,
+    })
+}
+/// let name [: type] [= expr];
+/// stack local
+fn parse_variable_declaration(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let (name, kind, value) = parse_declaration(token_stream)?;
+    Ok(ASTnode::VariableDeclaration {
+        name: name.lexeme,
+        kind: kind.map(Box::new),
+        value: value.map(Box::new),
+        addressed: false,
+        metadata: name.metadata,
+    })
+}
+/// let (a, _, c): (A, B, C) = value;
+fn parse_tuple_declaration(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let open = token_stream.expect(TokenKind::OpenParenth, "expected `(`")?;
+    let mut bindings: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedParenth) {
+        let binding = token_stream.expect(TokenKind::Identifer, "expected binding name")?;
+        bindings.push(if binding.lexeme == "_" {
+            ASTnode::Wildcard {
+                metadata: binding.metadata,
+            }
+        } else {
+            ASTnode::VariableDeclaration {
+                name: binding.lexeme,
+                kind: None,
+                value: None,
+                addressed: false,
+                metadata: binding.metadata,
+            }
+        });
+        if !token_stream.peek_is(TokenKind::ClosedParenth) {
+            token_stream.expect(TokenKind::Comma, "expected `,` or `)` after binding")?;
+        }
+    }
+    token_stream.next();
+    let kind = if token_stream.peek_is(TokenKind::Colon) {
+        token_stream.next();
+        Some(Box::new(parse_type(token_stream)?))
+    } else {
+        None
+    };
+    token_stream.expect(
+        TokenKind::Assign,
+        "expected `=`, a tuple has to be destructured from a value",
+    )?;
+    let value = parse_expr(token_stream)?;
+    token_stream.expect(TokenKind::SemiColon, "expected `;` after declaration")?;
+    Ok(ASTnode::TupleDeclaration {
+        bindings,
+        kind,
+        value: Box::new(value),
+        metadata: open.metadata,
+    })
+}
+/// shared tail of declarations, `name [: type] [= expr];`
+/// the type may only be omitted when it can be inferred from the initializer
+fn parse_declaration(
+    token_stream: &mut TokenStream,
+) -> Result<(Token, Option<ASTnode>, Option<ASTnode>), CompileError> {
+    let name = token_stream.expect(TokenKind::Identifer, "expected variable name")?;
+    let kind = if token_stream.peek_is(TokenKind::Colon) {
+        token_stream.next();
+        Some(parse_type(token_stream)?)
+    } else {
+        None
+    };
+    let value = if token_stream.peek_is(TokenKind::Assign) {
+        token_stream.next();
+        Some(parse_expr(token_stream)?)
+    } else {
+        None
+    };
+    if kind.is_none() && value.is_none() {
+        return Err(CompileError::new(
+            "type annotation needed, cannot infer type without an initializer".to_string(),
+        )
+        .attach_token(name)
+        .fmt_metadata(&token_stream.source));
+    }
+    token_stream.expect(TokenKind::SemiColon, "expected `;` after declaration")?;
+    Ok((name, kind, value))
+}
+fn parse_expr(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    parse_binary(token_stream, 0)
+}
+/// binding power of binary operators, higher binds tighter. all are left associative.
+fn binary_precedence(kind: &TokenKind) -> Option<u8> {
+    match kind {
+        TokenKind::DoublePipe => Some(1),
+        TokenKind::DoubleAnd => Some(2),
+        TokenKind::Pipe => Some(3),
+        TokenKind::Carot => Some(4),
+        TokenKind::And => Some(5),
+        TokenKind::Compare | TokenKind::NotEqual => Some(6),
+        TokenKind::Lesser | TokenKind::Greater | TokenKind::LesserEq | TokenKind::GreaterEq => {
+            Some(7)
+        }
+        TokenKind::ShiftLeft | TokenKind::ShiftRight => Some(8),
+        TokenKind::Plus | TokenKind::Dash => Some(9),
+        TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Some(10),
+        _ => None,
+    }
+}
+/// the operation of a compound assignment token, `+=` is Add
+fn assign_operator(kind: &TokenKind) -> Option<AssignOperator> {
+    Some(match kind {
+        TokenKind::PlusAssign => AssignOperator::Add,
+        TokenKind::DashAssign => AssignOperator::Sub,
+        TokenKind::StarAssign => AssignOperator::Mult,
+        TokenKind::SlashAssign => AssignOperator::Div,
+        TokenKind::PercentAssign => AssignOperator::Mod,
+        TokenKind::AndAssign => AssignOperator::And,
+        TokenKind::PipeAssign => AssignOperator::Or,
+        TokenKind::CarotAssign => AssignOperator::Xor,
+        TokenKind::ShiftLeftAssign => AssignOperator::ShiftLeft,
+        TokenKind::ShiftRightAssign => AssignOperator::ShiftRight,
+        _ => return None,
+    })
+}
+/// precedence climbing, consumes operators binding at least as tight as `min_precedence`
+fn parse_binary(
+    token_stream: &mut TokenStream,
+    min_precedence: u8,
+) -> Result<ASTnode, CompileError> {
+    let mut left = parse_cast(token_stream)?;
+    loop {
+        let precedence = match token_stream.peek().and_then(|t| binary_precedence(&t.kind)) {
+            Some(p) if p >= min_precedence => p,
+            _ => break,
+        };
+        let operator = token_stream.next().unwrap();
+        let right = parse_binary(token_stream, precedence + 1)?;
+        let op = BinaryOP {
+            left: Box::new(left),
+            right: Box::new(right),
+            metadata: operator.metadata,
+        };
+        left = match operator.kind {
+            TokenKind::DoublePipe => ASTnode::LogicalOr(op),
+            TokenKind::DoubleAnd => ASTnode::LogicalAnd(op),
+            TokenKind::Pipe => ASTnode::Or(op),
+            TokenKind::Carot => ASTnode::Xor(op),
+            TokenKind::ShiftLeft => ASTnode::ShiftLeft(op),
+            TokenKind::ShiftRight => ASTnode::ShiftRight(op),
+            TokenKind::And => ASTnode::And(op),
+            TokenKind::Compare => ASTnode::Equal(op),
+            TokenKind::NotEqual => ASTnode::NotEqual(op),
+            TokenKind::Lesser => ASTnode::Lesser(op),
+            TokenKind::Greater => ASTnode::Greater(op),
+            TokenKind::LesserEq => ASTnode::LesserEq(op),
+            TokenKind::GreaterEq => ASTnode::GreaterEq(op),
+            TokenKind::Plus => ASTnode::Add(op),
+            TokenKind::Dash => ASTnode::Sub(op),
+            TokenKind::Star => ASTnode::Mult(op),
+            TokenKind::Slash => ASTnode::Div(op),
+            TokenKind::Percent => ASTnode::Mod(op),
+            _ => unreachable!(),
+        };
+    }
+    Ok(left)
+}
+/// value as T, binds tighter than any binary operator and looser than unary ones
+fn parse_cast(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let mut value = parse_unary(token_stream)?;
+    while token_stream.peek_is(TokenKind::KWas) {
+        let token = token_stream.next().unwrap();
+        value = ASTnode::Cast {
+            value: Box::new(value),
+            kind: Box::new(parse_type(token_stream)?),
+            conversion: Conversion::Keep,
+            metadata: token.metadata,
+        };
+    }
+    Ok(value)
+}
+fn parse_unary(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    match token_stream.peek().map(|t| t.kind.clone()) {
+        Some(TokenKind::Dash) => {
+            let token = token_stream.next().unwrap();
+            Ok(ASTnode::Negate {
+                value: Box::new(parse_unary(token_stream)?),
+                metadata: token.metadata,
+            })
+        }
+        Some(TokenKind::Exclaim) => {
+            let token = token_stream.next().unwrap();
+            Ok(ASTnode::Not {
+                value: Box::new(parse_unary(token_stream)?),
+                metadata: token.metadata,
+            })
+        }
+        Some(TokenKind::Tilde) => {
+            let token = token_stream.next().unwrap();
+            Ok(ASTnode::BitNot {
+                value: Box::new(parse_unary(token_stream)?),
+                metadata: token.metadata,
+            })
+        }
+        Some(TokenKind::And) => {
+            let token = token_stream.next().unwrap();
+            Ok(ASTnode::AddressOf {
+                value: Box::new(parse_unary(token_stream)?),
+                metadata: token.metadata,
+            })
+        }
+        Some(TokenKind::Star) => {
+            let token = token_stream.next().unwrap();
+            Ok(ASTnode::Deref {
+                value: Box::new(parse_unary(token_stream)?),
+                metadata: token.metadata,
+            })
+        }
+        _ => parse_postfix(token_stream),
+    }
+}
+/// field access and indexing following a primary expression, `a.b[i].c`, tuple elements are
+/// accessed as numbered fields `t.0`
+fn parse_postfix(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let mut value = parse_primary(token_stream)?;
+    loop {
+        if token_stream.peek_is(TokenKind::Dot)
+            && token_stream
+                .peek_nth(1)
+                .is_some_and(|t| t.is(TokenKind::Identifer) || t.is(TokenKind::NumberLiteral))
+        {
+            let dot = token_stream.next().unwrap();
+            let field = token_stream.next().unwrap();
+            value = ASTnode::FieldAccess {
+                value: Box::new(value),
+                field: field.lexeme,
+                metadata: dot.metadata,
+            };
+        } else if token_stream.peek_is(TokenKind::OpenBracket) {
+            let bracket = token_stream.next().unwrap();
+            let index = parse_expr(token_stream)?;
+            token_stream.expect(TokenKind::ClosedBracket, "expected `]` after index")?;
+            value = ASTnode::Index {
+                value: Box::new(value),
+                index: Box::new(index),
+                checked: false,
+                metadata: bracket.metadata,
+            };
+        } else if token_stream.peek_is(TokenKind::OpenParenth) {
+            let parenth = token_stream.next().unwrap();
+            let params = parse_expr_list(token_stream, TokenKind::ClosedParenth)?;
+            value = ASTnode::Call {
+                callee: Box::new(value),
+                params,
+                metadata: parenth.metadata,
+            };
+        } else {
+            return Ok(value);
+        }
+    }
+}
+fn parse_primary(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
+    let token = token_stream.next().unwrap();
+    match token.kind {
+        TokenKind::NumberLiteral => {
+            let value = parse_number(&token.lexeme).ok_or_else(|| {
+                CompileError::new("invalid number literal".to_string())
+                    .attach_token(token.clone())
+                    .fmt_metadata(&token_stream.source)
+            })?;
+            Ok(ASTnode::NumberLiteral {
+                value,
+                metadata: token.metadata,
+            })
+        }
+        TokenKind::KWtrue | TokenKind::KWfalse => Ok(ASTnode::BoolLiteral {
+            value: token.is(TokenKind::KWtrue),
+            metadata: token.metadata,
+        }),
+        TokenKind::StringLiteral => Ok(ASTnode::StringLiteral {
+            value: token.lexeme,
+            metadata: token.metadata,
+        }),
+        TokenKind::CharLiteral => Ok(ASTnode::CharLiteral {
+            value: token.lexeme.chars().next().unwrap(),
+            metadata: token.metadata,
+        }),
+        TokenKind::Identifer => {
+            if matches!(token.lexeme.as_str(), "sizeof" | "alignof" | "offsetof")
+                && token_stream.peek_is(TokenKind::OpenParenth)
+            {
+                parse_builtin(token_stream, token)
+            } else if matches!(token.lexeme.as_str(), "va_start" | "va_arg")
+                && token_stream.peek_is(TokenKind::OpenParenth)
+            {
+                parse_va(token_stream, token)
+            } else if token_stream.peek_is(TokenKind::OpenParenth) {
+                token_stream.next();
+                let params = parse_expr_list(token_stream, TokenKind::ClosedParenth)?;
+                Ok(ASTnode::FunctionCall {
+                    name: token.lexeme,
+                    params,
+                    metadata: token.metadata,
+                })
+            } else if token_stream.peek_is(TokenKind::DoubleColon) {
+                let mut segments = vec![token.lexeme];
+                while token_stream.peek_is(TokenKind::DoubleColon) {
+                    token_stream.next();
+                    let segment =
+                        token_stream.expect(TokenKind::Identifer, "expected name after `::`")?;
+                    segments.push(segment.lexeme);
+                }
+                let path = ASTnode::Path {
+                    segments,
+                    metadata: token.metadata.clone(),
+                };
+                if token_stream.peek_is(TokenKind::OpenParenth) {
+                    token_stream.next();
+                    let params = parse_expr_list(token_stream, TokenKind::ClosedParenth)?;
+                    Ok(ASTnode::Call {
+                        callee: Box::new(path),
+                        params,
                         metadata: token.metadata,
+                    })
+                } else if is_struct_literal(token_stream) {
+                    let ASTnode::Path { segments, .. } = path else {
+                        unreachable!()
                     };
-                    return Ok(return_node);
+                    let name = Token {
+                        lexeme: segments.join("::"),
+                        ..token
+                    };
+                    parse_struct_literal(token_stream, name)
                 } else {
-                    return Err(CompileError::new("expected equal sign".to_string())
-                        .attach_token(token)
+                    Ok(path)
+                }
+            } else if is_struct_literal(token_stream) {
+                parse_struct_literal(token_stream, token)
+            } else {
+                Ok(ASTnode::Identifier {
+                    name: token.lexeme,
+                    metadata: token.metadata,
+                })
+            }
+        }
+        // (expr) or (a, b, ...) / (a,) / ()
+        TokenKind::OpenParenth => {
+            if token_stream.peek_is(TokenKind::ClosedParenth) {
+                token_stream.next();
+                return Ok(ASTnode::TupleLiteral {
+                    elements: Vec::new(),
+                    metadata: token.metadata,
+                });
+            }
+            let first = parse_expr(token_stream)?;
+            if token_stream.peek_is(TokenKind::ClosedParenth) {
+                token_stream.next();
+                return Ok(first);
+            }
+            token_stream.expect(TokenKind::Comma, "expected `,` or `)`")?;
+            let mut elements = vec![first];
+            elements.extend(parse_expr_list(token_stream, TokenKind::ClosedParenth)?);
+            Ok(ASTnode::TupleLiteral {
+                elements,
+                metadata: token.metadata,
+            })
+        }
+        // [a, b, ...] or [value; N]
+        TokenKind::OpenBracket => {
+            if token_stream.peek_is(TokenKind::ClosedBracket) {
+                token_stream.next();
+                return Ok(ASTnode::ArrayLiteral {
+                    elements: Vec::new(),
+                    metadata: token.metadata,
+                });
+            }
+            let first = parse_expr(token_stream)?;
+            if token_stream.peek_is(TokenKind::SemiColon) {
+                token_stream.next();
+                let length = parse_length(token_stream)?;
+                token_stream.expect(TokenKind::ClosedBracket, "expected `]`")?;
+                return Ok(ASTnode::ArrayRepeat {
+                    value: Box::new(first),
+                    length,
+                    metadata: token.metadata,
+                });
+            }
+            let mut elements = vec![first];
+            if !token_stream.peek_is(TokenKind::ClosedBracket) {
+                token_stream.expect(TokenKind::Comma, "expected `,`, `;` or `]`")?;
+            }
+            elements.extend(parse_expr_list(token_stream, TokenKind::ClosedBracket)?);
+            Ok(ASTnode::ArrayLiteral {
+                elements,
+                metadata: token.metadata,
+            })
+        }
+        _ => Err(CompileError::new("expected expression".to_string())
+            .attach_token(token)
+            .fmt_metadata(&token_stream.source)),
+    }
+}
+/// sizeof(T), alignof(T) or offsetof(T, field.field), they take a type where a call takes
+/// expressions
+fn parse_builtin(token_stream: &mut TokenStream, name: Token) -> Result<ASTnode, CompileError> {
+    token_stream.expect(TokenKind::OpenParenth, "expected `(`")?;
+    let kind = Box::new(parse_type(token_stream)?);
+    let node = match name.lexeme.as_str() {
+        "sizeof" => ASTnode::SizeOf {
+            kind,
+            metadata: name.metadata,
+        },
+        "alignof" => ASTnode::AlignOf {
+            kind,
+            metadata: name.metadata,
+        },
+        _ => {
+            token_stream.expect(
+                TokenKind::Comma,
+                "expected `,` and a field name after the type",
+            )?;
+            let mut fields: Vec<String> = Vec::new();
+            loop {
+                let field = token_stream.next().unwrap();
+                if !field.is(TokenKind::Identifer) && !field.is(TokenKind::NumberLiteral) {
+                    return Err(CompileError::new("expected field name".to_string())
+                        .attach_token(field)
                         .fmt_metadata(&token_stream.source));
                 }
+                fields.push(field.lexeme);
+                if !token_stream.peek_is(TokenKind::Dot) {
+                    break;
+                }
+                token_stream.next();
             }
-        };
-
-        // if token.is(TokenKind::Identifer) {
-        // } else {
-        //     return Err(CompileError::new("expected variable name".to_string())
-        //         .attach_token(token)
-        //         .fmt_metadata(&token_stream.source));
-        // }
+            ASTnode::OffsetOf {
+                kind,
+                fields,
+                metadata: name.metadata,
+            }
+        }
+    };
+    token_stream.expect(TokenKind::ClosedParenth, "expected `)`")?;
+    Ok(node)
+}
+/// va_start() or va_arg(list, T)
+fn parse_va(token_stream: &mut TokenStream, name: Token) -> Result<ASTnode, CompileError> {
+    token_stream.expect(TokenKind::OpenParenth, "expected `(`")?;
+    let node = if name.lexeme == "va_start" {
+        ASTnode::VaStart {
+            metadata: name.metadata,
+        }
+    } else {
+        let list = parse_expr(token_stream)?;
+        token_stream.expect(TokenKind::Comma, "expected `,` and a type after the list")?;
+        ASTnode::VaArg {
+            list: Box::new(list),
+            kind: Box::new(parse_type(token_stream)?),
+            metadata: name.metadata,
+        }
+    };
+    token_stream.expect(TokenKind::ClosedParenth, "expected `)`")?;
+    Ok(node)
+}
+/// `{ name:` after an identifier starts a struct literal, anything else is left for the caller
+/// so that `if x { ... }` still parses as a condition followed by a block
+fn is_struct_literal(token_stream: &TokenStream) -> bool {
+    token_stream.peek_is(TokenKind::OpenCurly)
+        && token_stream
+            .peek_nth(1)
+            .is_some_and(|t| t.is(TokenKind::Identifer))
+        && token_stream
+            .peek_nth(2)
+            .is_some_and(|t| t.is(TokenKind::Colon))
+}
+/// Name { field: expr, ... }
+fn parse_struct_literal(
+    token_stream: &mut TokenStream,
+    name: Token,
+) -> Result<ASTnode, CompileError> {
+    token_stream.expect(TokenKind::OpenCurly, "expected `{`")?;
+    let mut fields: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(TokenKind::ClosedCurly) {
+        let field = token_stream.expect(TokenKind::Identifer, "expected field name")?;
+        token_stream.expect(TokenKind::Colon, "expected colon after field name")?;
+        let value = parse_expr(token_stream)?;
+        fields.push(ASTnode::FieldValue {
+            name: field.lexeme,
+            value: Box::new(value),
+            metadata: field.metadata,
+        });
+        if !token_stream.peek_is(TokenKind::ClosedCurly) {
+            token_stream.expect(TokenKind::Comma, "expected `,` or `}` after field")?;
+        }
     }
-    todo!("const")
+    token_stream.next();
+    Ok(ASTnode::StructLiteral {
+        name: name.lexeme,
+        fields,
+        metadata: name.metadata,
+    })
 }
-fn parse_static(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
-    todo!("static")
+/// comma separated expressions up to and including `end`, allows a trailing comma
+fn parse_expr_list(
+    token_stream: &mut TokenStream,
+    end: TokenKind,
+) -> Result<Vec<ASTnode>, CompileError> {
+    let mut list: Vec<ASTnode> = Vec::new();
+    while !token_stream.peek_is(end.clone()) {
+        list.push(parse_expr(token_stream)?);
+        if !token_stream.peek_is(end.clone()) {
+            token_stream.expect(TokenKind::Comma, "expected `,` between expressions")?;
+        }
+    }
+    token_stream.next();
+    Ok(list)
 }
-fn parse_variable_declaration(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
-    todo!("variable_declaration")
+/// array length, a number literal
+fn parse_length(token_stream: &mut TokenStream) -> Result<usize, CompileError> {
+    let token = token_stream.expect(TokenKind::NumberLiteral, "expected array length")?;
+    parse_number(&token.lexeme).ok_or_else(|| {
+        CompileError::new("invalid number literal".to_string())
+            .attach_token(token)
+            .fmt_metadata(&token_stream.source)
+    })
 }
-fn parse_expr(token_stream: &mut TokenStream) -> Result<ASTnode, CompileError> {
-    todo!("expr")
+/// decimal, 0x hexadecimal, 0o octal and 0b binary literals
+fn parse_number(lexeme: &str) -> Option<usize> {
+    let (digits, radix) = match lexeme.get(..2) {
+        Some("0x") => (&lexeme[2..], 16),
+        Some("0o") => (&lexeme[2..], 8),
+        Some("0b") => (&lexeme[2..], 2),
+        _ => (lexeme, 10),
+    };
+    usize::from_str_radix(&digits.replace('_', ""), radix).ok()
 }
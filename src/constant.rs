@@ -1,5 +1,20 @@
+#[allow(dead_code)]
 pub const COMMENT_CHAR: char = '/';
 // pub const IDEINTIFIER_DELIMITATORS :&[char] = [' ','\t','"','\'',''];
 pub const SYSTEM_LIB_ROOT: &str = "./ansl-systemlib/";
 pub const SOURCE_FILE_EXTENSION: &str = ".ansl";
 pub const NAME: &str = "anslc";
+/// type given to integer literals that have nothing else to infer a type from
+pub const DEFAULT_INTEGER_TYPE: &str = "i32";
+/// fewest arms a dense match needs before it is compiled to a jump table
+pub const JUMP_TABLE_MIN_ARMS: usize = 4;
+/// size of a pointer in bytes, a full NISVC register
+pub const POINTER_SIZE: usize = 8;
+/// general purpose registers an asm block can bind values to
+pub const ASM_REGISTERS: [&str; 12] = [
+    "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12",
+];
+/// bytes each variadic argument takes on the stack
+pub const VARIADIC_SLOT_SIZE: usize = 8;
+/// joins the module path and the name of an item in its label, it can not appear in a name
+pub const MANGLE_SEPARATOR: &str = "$";
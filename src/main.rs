@@ -1,27 +1,44 @@
 use crate::constant::NAME;
 use ast::root_parse;
 use colorize::AnsiColor;
+use module::resolve_modules;
+use sema::analyze;
 use token::TokenStream;
 mod ast;
 mod constant;
+mod module;
+mod sema;
 mod token;
 mod util;
 static mut VERBOSE_FLAG: usize = 3;
 fn main() {
     let cli_args: Vec<String> = std::env::args().collect();
-    let entry_main = if let Some(s) = cli_args.get(1) {
+    let entry_main = if let Some(s) = cli_args.iter().skip(1).find(|a| !a.starts_with("--")) {
         s
     } else {
         panic!("no file");
     };
+    // trap on out of range array indices at runtime
+    let bounds_check = cli_args.iter().any(|a| a == "--bounds-check");
     let mut token_stream = TokenStream::new();
     match token_stream.tokenize_source_tree(entry_main) {
         Ok(()) => (),
         Err(e) => println!("{e}"),
     };
     println!("token stream :\n {token_stream}");
-    match root_parse(token_stream) {
-        Ok(ast) => println!("{ast:?}"),
+    let mut ast = match root_parse(&mut token_stream) {
+        Ok(ast) => ast,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+    if let Err(e) = resolve_modules(&mut ast, &token_stream.source) {
+        println!("{e}");
+        return;
+    }
+    match analyze(&mut ast, &token_stream.source, bounds_check) {
+        Ok(()) => println!("{ast:?}"),
         Err(e) => println!("{e}"),
     };
 }
@@ -51,13 +68,13 @@ fn _very_very_verbose_println(msg: &str) {
 
 #[macro_export]
 macro_rules! verbose_println {
-    ($($arg:tt)*) => (crate::_verbose_println(&format!($($arg)*)));
+    ($($arg:tt)*) => ($crate::_verbose_println(&format!($($arg)*)));
 }
 #[macro_export]
 macro_rules! very_verbose_println {
-    ($($arg:tt)*) => (crate::_very_verbose_println(&format!($($arg)*)));
+    ($($arg:tt)*) => ($crate::_very_verbose_println(&format!($($arg)*)));
 }
 #[macro_export]
 macro_rules! very_very_verbose_println {
-    ($($arg:tt)*) => (crate::_very_very_verbose_println(&format!($($arg)*)));
+    ($($arg:tt)*) => ($crate::_very_very_verbose_println(&format!($($arg)*)));
 }
@@ -0,0 +1,764 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::ASTnode,
+    constant::MANGLE_SEPARATOR,
+    token::{CompileError, MetadataReference, PrimitiveType, Source},
+};
+
+/// module resolution, runs between parsing and semantic analysis.
+/// resolves `use` imports and `a::b` paths, renames the items of every module to their mangled
+/// label and merges them into `root`, leaving a tree without modules. the items of an `impl` are
+/// merged as well, labelled after their type.
+pub fn resolve_modules(root: &mut ASTnode, source: &Source) -> Result<(), CompileError> {
+    let mut resolver = Resolver {
+        namespaces: HashMap::new(),
+        module: Vec::new(),
+        scopes: Vec::new(),
+        associated: HashMap::new(),
+        impl_type: None,
+    };
+    resolver
+        .declare(root, &[])
+        .and_then(|()| resolver.import(root, &[]))
+        .and_then(|()| resolver.implement(root, &[]))
+        .and_then(|()| resolver.rewrite(root, &[]))
+        .map_err(|e| e.fmt_metadata(source))?;
+    flatten(root);
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ItemKind {
+    Function,
+    Type,
+    /// const or static
+    Value,
+    Module,
+}
+
+/// what a name in a module refers to
+#[derive(Clone)]
+struct Item {
+    kind: ItemKind,
+    /// the name the item is emitted as, mangled with the path of its module
+    label: String,
+    /// full path of the item, for a module where its own items are found
+    path: Vec<String>,
+    public: bool,
+    /// module whose namespace holds the item, a private item is only seen from there and from
+    /// the modules inside of it
+    owner: Vec<String>,
+}
+
+struct Resolver {
+    /// module path to the names declared in or imported into the module
+    namespaces: HashMap<Vec<String>, HashMap<String, Item>>,
+    /// path of the module being rewritten
+    module: Vec<String>,
+    /// local names of the function being rewritten, innermost last, they shadow items
+    scopes: Vec<HashSet<String>>,
+    /// type label to the names of the methods and consts of its impls
+    associated: HashMap<String, HashSet<String>>,
+    /// label of the type whose impl is being rewritten, what `Self` names
+    impl_type: Option<String>,
+}
+
+/// label of `name` declared in `module`, items of the root module keep their name
+fn mangle(module: &[String], name: &str) -> String {
+    if module.is_empty() {
+        return name.to_string();
+    }
+    module.join(MANGLE_SEPARATOR) + MANGLE_SEPARATOR + name
+}
+
+impl Resolver {
+    /// fills the namespace of `module` with its items and those of its submodules
+    fn declare(&mut self, root: &ASTnode, module: &[String]) -> Result<(), CompileError> {
+        let ASTnode::Root {
+            types,
+            globals,
+            functions,
+            modules,
+            ..
+        } = root
+        else {
+            unreachable!()
+        };
+        let mut namespace: HashMap<String, Item> = HashMap::new();
+        for node in types.iter().chain(globals).chain(functions).chain(modules) {
+            let (kind, name, public, external, metadata) = match node {
+                ASTnode::FunctionDefinition {
+                    name,
+                    public,
+                    metadata,
+                    ..
+                } => (ItemKind::Function, name, *public, false, metadata),
+                ASTnode::ExternFunction {
+                    name,
+                    public,
+                    metadata,
+                    ..
+                } => (ItemKind::Function, name, *public, true, metadata),
+                ASTnode::StructDeclaration {
+                    name,
+                    public,
+                    metadata,
+                    ..
+                }
+                | ASTnode::EnumDeclaration {
+                    name,
+                    public,
+                    metadata,
+                    ..
+                } => (ItemKind::Type, name, *public, false, metadata),
+                ASTnode::ConstDeclaration {
+                    name,
+                    public,
+                    metadata,
+                    ..
+                } => (ItemKind::Value, name, *public, false, metadata),
+                ASTnode::StaticDeclaration {
+                    name,
+                    public,
+                    external,
+                    metadata,
+                    ..
+                } => (ItemKind::Value, name, *public, *external, metadata),
+                ASTnode::Module {
+                    name,
+                    public,
+                    root,
+                    metadata,
+                } => {
+                    let mut path = module.to_vec();
+                    path.push(name.clone());
+                    self.declare(root, &path)?;
+                    (ItemKind::Module, name, *public, false, metadata)
+                }
+                _ => unreachable!(),
+            };
+            let mut path = module.to_vec();
+            path.push(name.clone());
+            // extern labels are defined outside of ansl and can not be renamed
+            let label = if external {
+                name.clone()
+            } else {
+                mangle(module, name)
+            };
+            let item = Item {
+                kind,
+                label,
+                path,
+                public,
+                owner: module.to_vec(),
+            };
+            if namespace.insert(name.clone(), item).is_some() {
+                return Err(CompileError::new(format!(
+                    "the name `{name}` is defined multiple times"
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+        }
+        self.namespaces.insert(module.to_vec(), namespace);
+        Ok(())
+    }
+
+    /// adds the `use` imports of `module` to its namespace, inner modules first so that their
+    /// imports can be imported again
+    fn import(&mut self, root: &ASTnode, module: &[String]) -> Result<(), CompileError> {
+        let ASTnode::Root {
+            modules, imports, ..
+        } = root
+        else {
+            unreachable!()
+        };
+        for node in modules {
+            let ASTnode::Module { name, root, .. } = node else {
+                unreachable!()
+            };
+            let mut path = module.to_vec();
+            path.push(name.clone());
+            self.import(root, &path)?;
+        }
+        self.module = module.to_vec();
+        for node in imports {
+            let ASTnode::Use {
+                path,
+                alias,
+                metadata,
+            } = node
+            else {
+                unreachable!()
+            };
+            let (item, rest) = self.resolve_path(path, metadata)?;
+            if !rest.is_empty() {
+                return Err(CompileError::new(format!(
+                    "cannot import `{}`, only items of a module can be imported",
+                    path.join("::")
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+            let name = alias.as_ref().unwrap_or(path.last().unwrap());
+            let item = Item {
+                public: false,
+                owner: module.to_vec(),
+                ..item
+            };
+            let namespace = self.namespaces.get_mut(module).unwrap();
+            if namespace.insert(name.clone(), item).is_some() {
+                return Err(CompileError::new(format!(
+                    "the name `{name}` is defined multiple times"
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// resolves the type of every impl of `module` and its submodules to its label and collects
+    /// the names of their items, so that `Type::item` paths can be told from enum variants
+    fn implement(&mut self, root: &mut ASTnode, module: &[String]) -> Result<(), CompileError> {
+        let ASTnode::Root { modules, impls, .. } = root else {
+            unreachable!()
+        };
+        for node in modules.iter_mut() {
+            let ASTnode::Module { name, root, .. } = node else {
+                unreachable!()
+            };
+            let mut path = module.to_vec();
+            path.push(name.clone());
+            self.implement(root, &path)?;
+        }
+        self.module = module.to_vec();
+        for node in impls.iter_mut() {
+            let ASTnode::Impl {
+                kind,
+                items,
+                metadata,
+            } = node
+            else {
+                unreachable!()
+            };
+            let label = match kind.as_mut() {
+                ASTnode::Type { name, metadata } => self.resolve_type(name, metadata)?,
+                _ => None,
+            };
+            let (Some(label), ASTnode::Type { name, .. }) = (label, kind.as_mut()) else {
+                return Err(CompileError::new(
+                    "an impl can only be written for a struct or an enum".to_string(),
+                )
+                .attach_metadata(metadata.clone()));
+            };
+            *name = label.clone();
+            let names = self.associated.entry(label).or_default();
+            for item in items.iter() {
+                let (ASTnode::FunctionDefinition { name, metadata, .. }
+                | ASTnode::ConstDeclaration { name, metadata, .. }) = item
+                else {
+                    unreachable!()
+                };
+                if !names.insert(name.clone()) {
+                    return Err(CompileError::new(format!(
+                        "the name `{name}` is defined multiple times"
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// label of `name` in the impls of the type labelled `kind`
+    fn associated_label(&self, kind: &str, name: &str) -> Option<String> {
+        self.associated
+            .get(kind)
+            .filter(|names| names.contains(name))
+            .map(|_| kind.to_string() + MANGLE_SEPARATOR + name)
+    }
+
+    /// the item named by the leading segments of `segments` and the segments left after it,
+    /// the variant of an enum. the first segment is looked up in the module being rewritten and
+    /// then in the root module
+    fn resolve_path<'a>(
+        &self,
+        segments: &'a [String],
+        metadata: &MetadataReference,
+    ) -> Result<(Item, &'a [String]), CompileError> {
+        let first = &segments[0];
+        let mut item = self.namespaces[&self.module]
+            .get(first)
+            .or_else(|| self.namespaces[&Vec::new()].get(first))
+            .ok_or_else(|| {
+                CompileError::new(format!("cannot find `{first}` in this module"))
+                    .attach_metadata(metadata.clone())
+            })?;
+        let mut rest = &segments[1..];
+        while item.kind == ItemKind::Module && !rest.is_empty() {
+            let name = &rest[0];
+            item = self.namespaces[&item.path].get(name).ok_or_else(|| {
+                CompileError::new(format!(
+                    "cannot find `{name}` in module `{}`",
+                    item.path.join("::")
+                ))
+                .attach_metadata(metadata.clone())
+            })?;
+            if !item.public && !self.module.starts_with(&item.owner) {
+                return Err(CompileError::new(format!(
+                    "`{name}` is private to module `{}`, mark it `pub` to use it here",
+                    item.owner.join("::")
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+            rest = &rest[1..];
+        }
+        Ok((item.clone(), rest))
+    }
+
+    /// label of a name used on its own, None for locals and names that are not items
+    fn resolve_name(&self, name: &str, kinds: &[ItemKind]) -> Option<String> {
+        if self.scopes.iter().any(|scope| scope.contains(name)) {
+            return None;
+        }
+        self.namespaces[&self.module]
+            .get(name)
+            .filter(|item| kinds.contains(&item.kind))
+            .map(|item| item.label.clone())
+    }
+
+    /// label of a type written as `Name` or `a::b::Name`
+    fn resolve_type(
+        &self,
+        name: &str,
+        metadata: &MetadataReference,
+    ) -> Result<Option<String>, CompileError> {
+        if PrimitiveType::from_name(name).is_some() {
+            return Ok(None);
+        }
+        if name == "Self" && self.impl_type.is_some() {
+            return Ok(self.impl_type.clone());
+        }
+        let segments: Vec<String> = name.split("::").map(str::to_string).collect();
+        if segments.len() == 1 {
+            return Ok(self.resolve_name(name, &[ItemKind::Type]));
+        }
+        let (item, rest) = self.resolve_path(&segments, metadata)?;
+        if item.kind != ItemKind::Type || !rest.is_empty() {
+            return Err(CompileError::new(format!("`{name}` is not a type"))
+                .attach_metadata(metadata.clone()));
+        }
+        Ok(Some(item.label))
+    }
+
+    /// renames the items of `module` and its submodules and rewrites the names they use
+    fn rewrite(&mut self, root: &mut ASTnode, module: &[String]) -> Result<(), CompileError> {
+        let ASTnode::Root {
+            types,
+            globals,
+            functions,
+            modules,
+            impls,
+            ..
+        } = root
+        else {
+            unreachable!()
+        };
+        for node in modules.iter_mut() {
+            let ASTnode::Module { name, root, .. } = node else {
+                unreachable!()
+            };
+            let mut path = module.to_vec();
+            path.push(name.clone());
+            self.rewrite(root, &path)?;
+        }
+        self.module = module.to_vec();
+        for node in types
+            .iter_mut()
+            .chain(globals.iter_mut())
+            .chain(functions.iter_mut())
+        {
+            match node {
+                ASTnode::FunctionDefinition { name, .. } => {
+                    *name = mangle(module, name);
+                    self.function(node)?;
+                }
+                ASTnode::StructDeclaration { name, .. }
+                | ASTnode::EnumDeclaration { name, .. }
+                | ASTnode::ConstDeclaration { name, .. }
+                | ASTnode::StaticDeclaration {
+                    name,
+                    external: false,
+                    ..
+                } => {
+                    *name = mangle(module, name);
+                    self.walk(node)?;
+                }
+                _ => self.walk(node)?,
+            }
+        }
+        // associated consts go ahead of the globals that may use them
+        let mut consts = Vec::new();
+        for node in impls.drain(..) {
+            let ASTnode::Impl { kind, items, .. } = node else {
+                unreachable!()
+            };
+            let ASTnode::Type { name: label, .. } = *kind else {
+                unreachable!()
+            };
+            self.impl_type = Some(label.clone());
+            for mut item in items {
+                match &mut item {
+                    ASTnode::FunctionDefinition { name, .. } => {
+                        *name = label.clone() + MANGLE_SEPARATOR + name;
+                        self.function(&mut item)?;
+                        functions.push(item);
+                    }
+                    ASTnode::ConstDeclaration { name, .. } => {
+                        *name = label.clone() + MANGLE_SEPARATOR + name;
+                        self.walk(&mut item)?;
+                        consts.push(item);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            self.impl_type = None;
+        }
+        globals.splice(0..0, consts);
+        Ok(())
+    }
+
+    /// rewrites the signature and body of a function, its parameters shadow items
+    fn function(&mut self, function: &mut ASTnode) -> Result<(), CompileError> {
+        let ASTnode::FunctionDefinition {
+            params,
+            return_type,
+            body,
+            ..
+        } = function
+        else {
+            unreachable!()
+        };
+        for param in params.iter_mut() {
+            self.walk(param)?;
+        }
+        if let Some(return_type) = return_type {
+            self.walk(return_type)?;
+        }
+        let names = params.iter().filter_map(|param| match param {
+            ASTnode::Variable { name, .. } => Some(name.clone()),
+            _ => None,
+        });
+        self.scopes.push(names.collect());
+        let result = self.walk(body);
+        self.scopes.pop();
+        result
+    }
+
+    /// rewrites the names used in a statement, expression or type
+    fn walk(&mut self, node: &mut ASTnode) -> Result<(), CompileError> {
+        match node {
+            ASTnode::Type { name, metadata } => {
+                if let Some(label) = self.resolve_type(name, metadata)? {
+                    *name = label;
+                }
+                Ok(())
+            }
+            ASTnode::Identifier { name, .. } => {
+                if let Some(label) = self.resolve_name(name, &[ItemKind::Function, ItemKind::Value])
+                {
+                    *name = label;
+                }
+                Ok(())
+            }
+            ASTnode::FunctionCall { name, params, .. } => {
+                if let Some(label) = self.resolve_name(name, &[ItemKind::Function, ItemKind::Value])
+                {
+                    *name = label;
+                }
+                params.iter_mut().try_for_each(|param| self.walk(param))
+            }
+            ASTnode::Path { segments, metadata } => {
+                let metadata = metadata.clone();
+                if let Some(name) = self.resolve_value(segments, &metadata)? {
+                    *node = ASTnode::Identifier { name, metadata };
+                }
+                Ok(())
+            }
+            ASTnode::Call {
+                callee,
+                params,
+                metadata,
+            } => {
+                for param in params.iter_mut() {
+                    self.walk(param)?;
+                }
+                let ASTnode::Path { segments, .. } = callee.as_mut() else {
+                    return self.walk(callee);
+                };
+                if let Some(name) = self.resolve_value(segments, metadata)? {
+                    *node = ASTnode::FunctionCall {
+                        name,
+                        params: std::mem::take(params),
+                        metadata: metadata.clone(),
+                    };
+                }
+                Ok(())
+            }
+            ASTnode::VariantPattern {
+                segments, metadata, ..
+            } => self.resolve_value(segments, metadata).map(|_| ()),
+            ASTnode::StructLiteral {
+                name,
+                fields,
+                metadata,
+            } => {
+                if let Some(label) = self.resolve_type(name, metadata)? {
+                    *name = label;
+                }
+                fields.iter_mut().try_for_each(|field| self.walk(field))
+            }
+            ASTnode::Block { nodes } => {
+                self.scopes.push(HashSet::new());
+                let result = nodes.iter_mut().try_for_each(|n| self.walk(n));
+                self.scopes.pop();
+                result
+            }
+            ASTnode::VariableDeclaration {
+                name, kind, value, ..
+            }
+            | ASTnode::StaticDeclaration {
+                name, kind, value, ..
+            } => {
+                kind.iter_mut().try_for_each(|kind| self.walk(kind))?;
+                value.iter_mut().try_for_each(|value| self.walk(value))?;
+                self.declare_local(name);
+                Ok(())
+            }
+            ASTnode::ConstDeclaration {
+                name, kind, value, ..
+            } => {
+                kind.iter_mut().try_for_each(|kind| self.walk(kind))?;
+                self.walk(value)?;
+                self.declare_local(name);
+                Ok(())
+            }
+            ASTnode::TupleDeclaration {
+                bindings,
+                kind,
+                value,
+                ..
+            } => {
+                kind.iter_mut().try_for_each(|kind| self.walk(kind))?;
+                self.walk(value)?;
+                for binding in bindings {
+                    if let ASTnode::VariableDeclaration { name, .. } = binding {
+                        self.declare_local(name);
+                    }
+                }
+                Ok(())
+            }
+            ASTnode::For {
+                variable,
+                iterable,
+                step,
+                body,
+                ..
+            } => {
+                self.walk(iterable)?;
+                step.iter_mut().try_for_each(|step| self.walk(step))?;
+                self.scopes.push(HashSet::from([variable.clone()]));
+                let result = self.walk(body);
+                self.scopes.pop();
+                result
+            }
+            ASTnode::MatchArm { pattern, body, .. } => {
+                self.walk(pattern)?;
+                let mut bindings = HashSet::new();
+                if let ASTnode::VariantPattern {
+                    bindings: names, ..
+                } = pattern.as_ref()
+                {
+                    for binding in names {
+                        if let ASTnode::Identifier { name, .. } = binding {
+                            bindings.insert(name.clone());
+                        }
+                    }
+                }
+                self.scopes.push(bindings);
+                let result = self.walk(body);
+                self.scopes.pop();
+                result
+            }
+            ASTnode::Match { value, arms, .. } => {
+                self.walk(value)?;
+                arms.iter_mut().try_for_each(|arm| self.walk(arm))
+            }
+            ASTnode::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                self.walk(condition)?;
+                self.walk(body)?;
+                else_body.iter_mut().try_for_each(|body| self.walk(body))
+            }
+            ASTnode::While {
+                condition, body, ..
+            } => {
+                self.walk(condition)?;
+                self.walk(body)
+            }
+            ASTnode::Loop { body, .. } => self.walk(body),
+            ASTnode::Return { value, .. } => {
+                value.iter_mut().try_for_each(|value| self.walk(value))
+            }
+            ASTnode::Assign { dest, expr, .. } | ASTnode::CompoundAssign { dest, expr, .. } => {
+                self.walk(dest)?;
+                self.walk(expr)
+            }
+            ASTnode::Asm { operands, .. } => operands
+                .iter_mut()
+                .try_for_each(|operand| self.walk(operand)),
+            ASTnode::StructDeclaration { fields, .. } => {
+                fields.iter_mut().try_for_each(|field| self.walk(field))
+            }
+            ASTnode::EnumDeclaration { kind, variants, .. } => {
+                kind.iter_mut().try_for_each(|kind| self.walk(kind))?;
+                variants
+                    .iter_mut()
+                    .try_for_each(|variant| self.walk(variant))
+            }
+            ASTnode::EnumVariant { value, fields, .. } => {
+                value.iter_mut().try_for_each(|value| self.walk(value))?;
+                fields.iter_mut().try_for_each(|field| self.walk(field))
+            }
+            ASTnode::ExternFunction {
+                params,
+                return_type,
+                ..
+            } => {
+                params.iter_mut().try_for_each(|param| self.walk(param))?;
+                return_type.iter_mut().try_for_each(|kind| self.walk(kind))
+            }
+            ASTnode::Field { kind, .. }
+            | ASTnode::Variable { kind, .. }
+            | ASTnode::PointerType { kind, .. }
+            | ASTnode::ArrayType { kind, .. }
+            | ASTnode::SizeOf { kind, .. }
+            | ASTnode::AlignOf { kind, .. }
+            | ASTnode::OffsetOf { kind, .. } => self.walk(kind),
+            ASTnode::TupleType { elements, .. } => elements
+                .iter_mut()
+                .try_for_each(|element| self.walk(element)),
+            ASTnode::FunctionType {
+                params,
+                return_type,
+                ..
+            } => {
+                params.iter_mut().try_for_each(|param| self.walk(param))?;
+                return_type.iter_mut().try_for_each(|kind| self.walk(kind))
+            }
+            ASTnode::Cast { value, kind, .. }
+            | ASTnode::VaArg {
+                list: value, kind, ..
+            } => {
+                self.walk(value)?;
+                self.walk(kind)
+            }
+            _ => node
+                .children_mut()
+                .into_iter()
+                .try_for_each(|child| self.walk(child)),
+        }
+    }
+
+    /// resolves the path of a value, `a::b::item`, `a::Type::item` or `a::Enum::Variant`. gives
+    /// the label of a function, const, static or item of an impl and rewrites the path of a
+    /// variant in place to `Enum::Variant` with the label of the enum, None when the path is a
+    /// variant or does not start with an item
+    fn resolve_value(
+        &self,
+        segments: &mut Vec<String>,
+        metadata: &MetadataReference,
+    ) -> Result<Option<String>, CompileError> {
+        if let (Some(label), 2) = (&self.impl_type, segments.len()) {
+            if segments[0] == "Self" {
+                segments[0] = label.clone();
+                return Ok(self.associated_label(label, &segments[1]));
+            }
+        }
+        let first = &segments[0];
+        if !self.namespaces[&self.module].contains_key(first)
+            && !self.namespaces[&Vec::new()].contains_key(first)
+        {
+            // left for semantic analysis to report
+            return Ok(None);
+        }
+        let (item, rest) = self.resolve_path(segments, metadata)?;
+        match (item.kind, rest.len()) {
+            (ItemKind::Function | ItemKind::Value, 0) => Ok(Some(item.label)),
+            (ItemKind::Type, 1) => {
+                if let Some(label) = self.associated_label(&item.label, &rest[0]) {
+                    return Ok(Some(label));
+                }
+                let variant = rest[0].clone();
+                *segments = vec![item.label, variant];
+                Ok(None)
+            }
+            _ => Err(
+                CompileError::new(format!("`{}` is not a value", segments.join("::")))
+                    .attach_metadata(metadata.clone()),
+            ),
+        }
+    }
+
+    fn declare_local(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+}
+
+/// moves the items of every module into `root`, ahead of its own items so that the globals of a
+/// module are declared before the code using them
+fn flatten(root: &mut ASTnode) {
+    let ASTnode::Root {
+        types,
+        globals,
+        functions,
+        modules,
+        imports,
+        // drained into the items by `rewrite`
+        ..
+    } = root
+    else {
+        unreachable!()
+    };
+    imports.clear();
+    let (mut all_types, mut all_globals, mut all_functions) = (Vec::new(), Vec::new(), Vec::new());
+    for module in modules.drain(..) {
+        let ASTnode::Module { mut root, .. } = module else {
+            unreachable!()
+        };
+        flatten(&mut root);
+        let ASTnode::Root {
+            types,
+            globals,
+            functions,
+            ..
+        } = *root
+        else {
+            unreachable!()
+        };
+        all_types.extend(types);
+        all_globals.extend(globals);
+        all_functions.extend(functions);
+    }
+    all_types.append(types);
+    all_globals.append(globals);
+    all_functions.append(functions);
+    *types = all_types;
+    *globals = all_globals;
+    *functions = all_functions;
+}
@@ -0,0 +1,2693 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{ASTnode, AsmDirection, AssignOperator, BinaryOP, Conversion},
+    constant::{
+        ASM_REGISTERS, DEFAULT_INTEGER_TYPE, JUMP_TABLE_MIN_ARMS, MANGLE_SEPARATOR, POINTER_SIZE,
+        VARIADIC_SLOT_SIZE,
+    },
+    token::{CompileError, MetadataReference, PrimitiveType, Source},
+};
+
+/// semantic analysis, runs over the parsed tree before assembly resolution.
+/// resolves names and fills in the types of declarations that left them to be inferred.
+/// with `bounds_check` array indices that are not known at compile time are checked at runtime.
+pub fn analyze(
+    root: &mut ASTnode,
+    source: &Source,
+    bounds_check: bool,
+) -> Result<(), CompileError> {
+    Analyzer::new(bounds_check)
+        .root(root)
+        .map_err(|e| e.fmt_metadata(source))
+}
+
+/// an enum, its discriminant is stored in a tag of type `kind` at offset 0 followed by the
+/// payload of the active variant. enums without payloads are just the tag.
+struct EnumInfo {
+    kind: PrimitiveType,
+    variants: Vec<VariantInfo>,
+    /// size and alignment, None until the payloads have been laid out
+    layout: Option<(usize, usize)>,
+}
+
+struct VariantInfo {
+    name: String,
+    discriminant: i128,
+    /// payload types
+    fields: Vec<ASTnode>,
+}
+
+/// memory layout of a struct, `fields` are in declaration order
+struct StructLayout {
+    /// name, type and byte offset of each field
+    fields: Vec<(String, ASTnode, usize)>,
+    size: usize,
+    align: usize,
+}
+
+/// a name in scope
+struct Binding {
+    kind: ASTnode,
+    /// false for consts and statics without `mut`, they can not be assigned
+    mutable: bool,
+}
+
+struct Analyzer {
+    enums: HashMap<String, EnumInfo>,
+    structs: HashMap<String, StructLayout>,
+    /// innermost scope last, the first scope holds globals
+    scopes: Vec<HashMap<String, Binding>>,
+    /// names in the matching scope whose address has been taken
+    addressed: Vec<HashSet<String>>,
+    /// function name to its FunctionType
+    functions: HashMap<String, ASTnode>,
+    /// whether the function being analyzed takes `...`
+    variadic: bool,
+    /// return type of the function being analyzed, None when it returns nothing
+    return_type: Option<ASTnode>,
+    /// `@format` functions, their variadic arguments are checked against their format string
+    formats: HashSet<String>,
+    /// labels of the loops enclosing the statement being analyzed, innermost last
+    loops: Vec<Option<String>>,
+    bounds_check: bool,
+}
+
+impl Analyzer {
+    fn new(bounds_check: bool) -> Self {
+        Self {
+            enums: HashMap::new(),
+            structs: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            addressed: vec![HashSet::new()],
+            functions: HashMap::new(),
+            variadic: false,
+            return_type: None,
+            formats: HashSet::new(),
+            loops: Vec::new(),
+            bounds_check,
+        }
+    }
+
+    fn root(&mut self, root: &mut ASTnode) -> Result<(), CompileError> {
+        // modules and imports have been merged into the items by module resolution
+        let ASTnode::Root {
+            types,
+            globals,
+            functions,
+            ..
+        } = root
+        else {
+            unreachable!()
+        };
+        for declaration in types.iter_mut() {
+            if let ASTnode::EnumDeclaration { .. } = declaration {
+                self.enum_declaration(declaration)?;
+            }
+        }
+        self.layout_types(types)?;
+        for function in functions.iter() {
+            let (ASTnode::FunctionDefinition {
+                name,
+                params,
+                return_type,
+                variadic,
+                format,
+                metadata,
+                ..
+            }
+            | ASTnode::ExternFunction {
+                name,
+                params,
+                return_type,
+                variadic,
+                format,
+                metadata,
+                ..
+            }) = function
+            else {
+                unreachable!()
+            };
+            let mut param_types = Vec::new();
+            for param in params {
+                if let ASTnode::Variable { kind, .. } = param {
+                    self.check_type(kind)?;
+                    param_types.push(kind.as_ref().clone());
+                }
+            }
+            if let Some(return_type) = return_type {
+                self.check_type(return_type)?;
+            }
+            if *format {
+                let takes_format = matches!(param_types.last(), Some(ASTnode::PointerType { kind, .. })
+                    if matches!(kind.as_ref(), ASTnode::Type { name, .. } if name == "u8"));
+                if !*variadic || !takes_format {
+                    return Err(CompileError::new(format!(
+                        "`@format` function `{name}` needs a `*u8` format string as its last \
+                         parameter, followed by `...`"
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                self.formats.insert(name.clone());
+            }
+            let kind = ASTnode::FunctionType {
+                params: param_types,
+                return_type: return_type.clone(),
+                variadic: *variadic,
+                metadata: metadata.clone(),
+            };
+            if let Some(existing) = self.functions.get(name) {
+                // modules can each declare the same extern function
+                let external = matches!(function, ASTnode::ExternFunction { .. });
+                if external && type_name(existing) == type_name(&kind) {
+                    continue;
+                }
+                return Err(CompileError::new(format!(
+                    "function `{name}` is defined multiple times"
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+            self.functions.insert(name.clone(), kind);
+        }
+        for global in globals.iter_mut() {
+            self.statement(global)?;
+        }
+        for function in functions.iter_mut() {
+            // an extern function has no body to analyze
+            if let ASTnode::FunctionDefinition { .. } = function {
+                self.function(function)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// numbers the variants of an enum and picks its tag type, rejecting duplicate discriminants
+    /// and discriminants that do not fit the tag
+    fn enum_declaration(&mut self, declaration: &mut ASTnode) -> Result<(), CompileError> {
+        let ASTnode::EnumDeclaration {
+            name,
+            kind,
+            variants,
+            metadata,
+            ..
+        } = declaration
+        else {
+            unreachable!()
+        };
+        if self.enums.contains_key(name) || PrimitiveType::from_name(name).is_some() {
+            return Err(
+                CompileError::new(format!("type `{name}` is defined multiple times"))
+                    .attach_metadata(metadata.clone()),
+            );
+        }
+        let mut infos: Vec<VariantInfo> = Vec::new();
+        let mut next = 0;
+        for variant in variants.iter_mut() {
+            let ASTnode::EnumVariant {
+                name: variant_name,
+                value,
+                fields,
+                discriminant,
+                metadata,
+            } = variant
+            else {
+                unreachable!()
+            };
+            *discriminant = value.as_deref().map(literal_value).unwrap_or(next);
+            for other in &infos {
+                if &other.name == variant_name {
+                    return Err(CompileError::new(format!(
+                        "variant `{variant_name}` is defined multiple times"
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                if other.discriminant == *discriminant {
+                    return Err(CompileError::new(format!(
+                        "discriminant {discriminant} of `{name}::{variant_name}` is already used by `{name}::{}`",
+                        other.name
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+            }
+            infos.push(VariantInfo {
+                name: variant_name.clone(),
+                discriminant: *discriminant,
+                fields: fields
+                    .iter()
+                    .map(|field| {
+                        let ASTnode::Field { kind, .. } = field else {
+                            unreachable!()
+                        };
+                        *kind.clone()
+                    })
+                    .collect(),
+            });
+            next = *discriminant + 1;
+        }
+        let tagged = infos.iter().any(|v| !v.fields.is_empty());
+        let kind = kind.get_or_insert_with(|| {
+            // C-like enums default to the default integer type, tagged unions get the smallest
+            // tag that holds every discriminant
+            let tag_name = if tagged {
+                let min = infos.iter().map(|v| v.discriminant).min().unwrap_or(0);
+                let max = infos.iter().map(|v| v.discriminant).max().unwrap_or(0);
+                ["u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64"]
+                    .into_iter()
+                    .find(|t| {
+                        let (low, high) = PrimitiveType::from_name(t).unwrap().range().unwrap();
+                        low <= min && max <= high
+                    })
+                    .unwrap_or("i64")
+            } else {
+                DEFAULT_INTEGER_TYPE
+            };
+            Box::new(ASTnode::Type {
+                name: tag_name.to_string(),
+                metadata: metadata.clone(),
+            })
+        });
+        let ASTnode::Type {
+            name: kind_name,
+            metadata: kind_metadata,
+        } = kind.as_ref()
+        else {
+            unreachable!()
+        };
+        let Some((primitive, (min, max))) = PrimitiveType::from_name(kind_name)
+            .filter(|p| *p != PrimitiveType::Bool)
+            .and_then(|p| p.range().map(|range| (p, range)))
+        else {
+            return Err(CompileError::new(format!(
+                "enum underlying type must be an integer primitive, found `{kind_name}`"
+            ))
+            .attach_metadata(kind_metadata.clone()));
+        };
+        for (info, variant) in infos.iter().zip(variants.iter()) {
+            if info.discriminant < min || info.discriminant > max {
+                let ASTnode::EnumVariant { metadata, .. } = variant else {
+                    unreachable!()
+                };
+                return Err(CompileError::new(format!(
+                    "discriminant {} of `{name}::{}` overflows `{kind_name}`",
+                    info.discriminant, info.name
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+        }
+        self.enums.insert(
+            name.clone(),
+            EnumInfo {
+                kind: primitive,
+                variants: infos,
+                layout: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// computes the layout of every struct and enum and writes it back into its declaration.
+    /// fields are placed in order, each at the next multiple of its alignment, a struct is aligned
+    /// to its most aligned field and padded to a multiple of that. packed structs have no padding.
+    /// the payload of each enum variant is laid out the same way, starting after the tag.
+    fn layout_types(&mut self, types: &mut [ASTnode]) -> Result<(), CompileError> {
+        let mut pending: Vec<&mut ASTnode> = Vec::new();
+        for declaration in types.iter_mut() {
+            if let ASTnode::StructDeclaration { name, metadata, .. } = declaration {
+                if pending
+                    .iter()
+                    .any(|d| type_declaration_name(d) == Some(name.as_str()))
+                    || PrimitiveType::from_name(name).is_some()
+                    || self.enums.contains_key(name)
+                {
+                    return Err(CompileError::new(format!(
+                        "type `{name}` is defined multiple times"
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+            }
+            pending.push(declaration);
+        }
+        // a type can be laid out once all of its field types are, repeat until none are left
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|declaration| {
+                declaration_fields(declaration).iter().all(|field| {
+                    let ASTnode::Field { kind, .. } = field else {
+                        unreachable!()
+                    };
+                    self.size_align(kind).is_ok()
+                })
+            });
+            let Some(ready) = ready else {
+                return Err(self.unresolvable_type(&pending));
+            };
+            match pending.swap_remove(ready) {
+                ASTnode::StructDeclaration {
+                    name,
+                    fields,
+                    packed,
+                    size,
+                    align,
+                    ..
+                } => {
+                    let mut layout = StructLayout {
+                        fields: Vec::new(),
+                        size: 0,
+                        align: 1,
+                    };
+                    for (i, field) in fields.iter().enumerate() {
+                        let ASTnode::Field {
+                            name: field_name,
+                            metadata,
+                            ..
+                        } = field
+                        else {
+                            unreachable!()
+                        };
+                        if fields[..i].iter().any(
+                            |other| matches!(other, ASTnode::Field { name, .. } if name == field_name),
+                        ) {
+                            return Err(CompileError::new(format!(
+                                "field `{field_name}` is declared multiple times"
+                            ))
+                            .attach_metadata(metadata.clone()));
+                        }
+                    }
+                    (layout.size, layout.align) = self.place_fields(fields, 0, 1, *packed)?;
+                    for field in fields.iter() {
+                        let ASTnode::Field {
+                            name, kind, offset, ..
+                        } = field
+                        else {
+                            unreachable!()
+                        };
+                        layout.fields.push((name.clone(), *kind.clone(), *offset));
+                    }
+                    layout.size = layout.size.next_multiple_of(layout.align);
+                    *size = layout.size;
+                    *align = layout.align;
+                    self.structs.insert(name.clone(), layout);
+                }
+                ASTnode::EnumDeclaration {
+                    name,
+                    variants,
+                    size,
+                    align,
+                    ..
+                } => {
+                    let tag = self.enums[name.as_str()].kind.size();
+                    let (mut enum_size, mut enum_align) = (tag, tag);
+                    for variant in variants.iter_mut() {
+                        let ASTnode::EnumVariant { fields, .. } = variant else {
+                            unreachable!()
+                        };
+                        let (end, variant_align) = self.place_fields(fields, tag, tag, false)?;
+                        enum_size = enum_size.max(end);
+                        enum_align = enum_align.max(variant_align);
+                    }
+                    *size = enum_size.next_multiple_of(enum_align);
+                    *align = enum_align;
+                    self.enums.get_mut(name.as_str()).unwrap().layout = Some((*size, *align));
+                }
+                _ => unreachable!(),
+            }
+        }
+        for declaration in types.iter() {
+            for field in declaration_fields(declaration) {
+                let ASTnode::Field { kind, .. } = field else {
+                    unreachable!()
+                };
+                self.check_type(kind)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// sets the offset of each Field in order starting at `start`,
+    /// returns the end of the last field and the largest alignment seen
+    fn place_fields(
+        &self,
+        fields: &mut [ASTnode],
+        start: usize,
+        align: usize,
+        packed: bool,
+    ) -> Result<(usize, usize), CompileError> {
+        let (mut end, mut align) = (start, align);
+        for field in fields.iter_mut() {
+            let ASTnode::Field { kind, offset, .. } = field else {
+                unreachable!()
+            };
+            let (field_size, field_align) = self.size_align(kind)?;
+            let field_align = if packed { 1 } else { field_align };
+            *offset = end.next_multiple_of(field_align);
+            end = *offset + field_size;
+            align = align.max(field_align);
+        }
+        Ok((end, align))
+    }
+
+    /// error for types that could not be laid out, either a field names an unknown type or
+    /// the types contain each other
+    fn unresolvable_type(&self, pending: &[&mut ASTnode]) -> CompileError {
+        let is_pending = |name: &str| {
+            pending
+                .iter()
+                .any(|d| type_declaration_name(d) == Some(name))
+        };
+        for declaration in pending {
+            for field in declaration_fields(declaration) {
+                let ASTnode::Field { kind, .. } = field else {
+                    unreachable!()
+                };
+                for kind in named_types(kind) {
+                    let ASTnode::Type { name, .. } = kind else {
+                        unreachable!()
+                    };
+                    if !is_pending(name) {
+                        if let Err(e) = self.size_align(kind) {
+                            return e;
+                        }
+                    }
+                }
+            }
+        }
+        let (ASTnode::StructDeclaration { name, metadata, .. }
+        | ASTnode::EnumDeclaration { name, metadata, .. }) = &*pending[0]
+        else {
+            unreachable!()
+        };
+        CompileError::new(format!(
+            "recursive type `{name}` has infinite size, it contains itself"
+        ))
+        .attach_metadata(metadata.clone())
+    }
+
+    /// size and alignment in bytes, primitives are aligned to their size
+    fn size_align(&self, kind: &ASTnode) -> Result<(usize, usize), CompileError> {
+        match kind {
+            ASTnode::Type { name, metadata } => {
+                if let Some(primitive) = PrimitiveType::from_name(name) {
+                    Ok((primitive.size(), primitive.size()))
+                } else if let Some(layout) = self.structs.get(name) {
+                    Ok((layout.size, layout.align))
+                } else if let Some((size, align)) =
+                    self.enums.get(name).and_then(|info| info.layout)
+                {
+                    Ok((size, align))
+                } else {
+                    Err(CompileError::new(format!("cannot find type `{name}`"))
+                        .attach_metadata(metadata.clone()))
+                }
+            }
+            // the pointee is not needed, which lets a struct point to itself
+            ASTnode::PointerType { .. } | ASTnode::FunctionType { .. } => {
+                Ok((POINTER_SIZE, POINTER_SIZE))
+            }
+            // elements are padded to their alignment already, so they are placed back to back
+            ASTnode::ArrayType { kind, length, .. } => {
+                let (size, align) = self.size_align(kind)?;
+                Ok((size * length, align))
+            }
+            ASTnode::TupleType { elements, .. } => {
+                let (_, size, align) = self.tuple_layout(elements)?;
+                Ok((size, align))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// offsets, size and alignment of a tuple, laid out like the fields of a struct
+    fn tuple_layout(
+        &self,
+        elements: &[ASTnode],
+    ) -> Result<(Vec<usize>, usize, usize), CompileError> {
+        let (mut offsets, mut end, mut align) = (Vec::new(), 0usize, 1);
+        for element in elements {
+            let (element_size, element_align) = self.size_align(element)?;
+            offsets.push(end.next_multiple_of(element_align));
+            end = offsets.last().unwrap() + element_size;
+            align = align.max(element_align);
+        }
+        Ok((offsets, end.next_multiple_of(align), align))
+    }
+
+    /// byte offset of a field inside of a struct or tuple and the type of the field
+    fn field_offset(
+        &self,
+        kind: &ASTnode,
+        field: &str,
+        metadata: &MetadataReference,
+    ) -> Result<(usize, ASTnode), CompileError> {
+        let found = match kind {
+            ASTnode::Type { name, .. } => self.structs.get(name).and_then(|layout| {
+                layout
+                    .fields
+                    .iter()
+                    .find(|(f, _, _)| f == field)
+                    .map(|(_, kind, offset)| (*offset, kind.clone()))
+            }),
+            ASTnode::TupleType { elements, .. } => {
+                let (offsets, _, _) = self.tuple_layout(elements)?;
+                field
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|i| *i < elements.len())
+                    .map(|i| (offsets[i], elements[i].clone()))
+            }
+            _ => None,
+        };
+        found.ok_or_else(|| {
+            CompileError::new(format!("type `{}` has no field `{field}`", type_name(kind)))
+                .attach_metadata(metadata.clone())
+        })
+    }
+
+    /// rejects types that do not exist
+    fn check_type(&self, kind: &ASTnode) -> Result<(), CompileError> {
+        match kind {
+            ASTnode::PointerType { kind, .. } | ASTnode::ArrayType { kind, .. } => {
+                self.check_type(kind)
+            }
+            ASTnode::TupleType { elements, .. } => elements
+                .iter()
+                .try_for_each(|element| self.check_type(element)),
+            ASTnode::FunctionType {
+                params,
+                return_type,
+                ..
+            } => params
+                .iter()
+                .chain(return_type.as_deref())
+                .try_for_each(|kind| self.check_type(kind)),
+            _ => self.size_align(kind).map(|_| ()),
+        }
+    }
+
+    fn function(&mut self, function: &mut ASTnode) -> Result<(), CompileError> {
+        let ASTnode::FunctionDefinition {
+            name,
+            params,
+            return_type,
+            body,
+            variadic,
+            naked,
+            metadata,
+            ..
+        } = function
+        else {
+            unreachable!()
+        };
+        self.variadic = *variadic;
+        self.return_type = return_type.as_deref().cloned();
+        let mut scope = HashMap::new();
+        for param in params.iter() {
+            if let ASTnode::Variable { name, kind, .. } = param {
+                scope.insert(name.clone(), kind.as_ref().clone());
+            }
+        }
+        self.push_scope(scope);
+        let result = self.statement(body);
+        let addressed = self.pop_scope();
+        // the body of a naked function leaves its value in r1 itself
+        if let Some(kind) = return_type.as_deref().filter(|_| !*naked) {
+            if result.is_ok() && type_name(kind) != "()" && !always_returns(body) {
+                return Err(CompileError::new(format!(
+                    "`{name}` returns `{}` but can reach the end of its body without a `return`",
+                    type_name(kind)
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+        }
+        for param in params.iter_mut() {
+            if let ASTnode::Variable {
+                name,
+                addressed: param_addressed,
+                ..
+            } = param
+            {
+                *param_addressed = addressed.contains(name);
+            }
+        }
+        result
+    }
+
+    /// opens a scope holding the variables of `scope`
+    fn push_scope(&mut self, scope: HashMap<String, ASTnode>) {
+        let scope = scope
+            .into_iter()
+            .map(|(name, kind)| {
+                (
+                    name,
+                    Binding {
+                        kind,
+                        mutable: true,
+                    },
+                )
+            })
+            .collect();
+        self.scopes.push(scope);
+        self.addressed.push(HashSet::new());
+    }
+
+    /// returns the names of the scope whose address was taken
+    fn pop_scope(&mut self) -> HashSet<String> {
+        self.scopes.pop();
+        self.addressed.pop().unwrap()
+    }
+
+    fn statement(&mut self, node: &mut ASTnode) -> Result<(), CompileError> {
+        let mutable = !matches!(
+            node,
+            ASTnode::StaticDeclaration { mutable: false, .. } | ASTnode::ConstDeclaration { .. }
+        );
+        match node {
+            ASTnode::Block { nodes } => {
+                self.push_scope(HashMap::new());
+                let result = nodes.iter_mut().try_for_each(|n| self.statement(n));
+                let addressed = self.pop_scope();
+                let declarations = nodes.iter_mut().flat_map(|node| match node {
+                    ASTnode::TupleDeclaration { bindings, .. } => bindings.iter_mut().collect(),
+                    node => vec![node],
+                });
+                for declaration in declarations {
+                    if let ASTnode::VariableDeclaration {
+                        name,
+                        addressed: declaration_addressed,
+                        ..
+                    } = declaration
+                    {
+                        *declaration_addressed = addressed.contains(name);
+                    }
+                }
+                result
+            }
+            ASTnode::VariableDeclaration {
+                name,
+                kind,
+                value,
+                metadata,
+                ..
+            }
+            | ASTnode::StaticDeclaration {
+                name,
+                kind,
+                value,
+                metadata,
+                ..
+            } => {
+                if let Some(v) = value {
+                    self.expr(v)?;
+                }
+                self.declare(name, kind, value.as_deref(), mutable, metadata)
+            }
+            ASTnode::TupleDeclaration {
+                bindings,
+                kind,
+                value,
+                metadata,
+            } => {
+                self.expr(value)?;
+                let value_kind = self.type_of(value)?;
+                let kind = match kind.as_deref() {
+                    Some(kind) => {
+                        self.check_type(kind)?;
+                        Some(kind)
+                    }
+                    None => value_kind.as_ref(),
+                };
+                let Some(ASTnode::TupleType { elements, .. }) = kind else {
+                    return Err(CompileError::new(format!(
+                        "cannot destructure a value of type `{}`, it is not a tuple",
+                        kind.map_or(DEFAULT_INTEGER_TYPE.to_string(), type_name)
+                    ))
+                    .attach_metadata(metadata.clone()));
+                };
+                if let Some(ASTnode::TupleType {
+                    elements: found, ..
+                }) = &value_kind
+                {
+                    if found.len() != elements.len() {
+                        return Err(CompileError::new(format!(
+                            "expected a tuple of {} elements, found {}",
+                            elements.len(),
+                            found.len()
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                }
+                if elements.len() != bindings.len() {
+                    return Err(CompileError::new(format!(
+                        "expected a tuple of {} elements, found {}",
+                        bindings.len(),
+                        elements.len()
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                for (binding, element) in bindings.iter_mut().zip(elements) {
+                    if let ASTnode::VariableDeclaration {
+                        name,
+                        kind,
+                        metadata,
+                        ..
+                    } = binding
+                    {
+                        *kind = Some(Box::new(element.clone()));
+                        self.declare(name, kind, None, true, metadata)?;
+                    }
+                }
+                Ok(())
+            }
+            ASTnode::ConstDeclaration {
+                name,
+                kind,
+                value,
+                metadata,
+                ..
+            } => {
+                self.expr(value)?;
+                self.declare(name, kind, Some(value), mutable, metadata)
+            }
+            ASTnode::If {
+                condition,
+                body,
+                else_body,
+                metadata,
+            } => {
+                self.expr(condition)?;
+                self.check_condition(condition, metadata)?;
+                self.statement(body)?;
+                if let Some(else_body) = else_body {
+                    self.statement(else_body)?;
+                }
+                Ok(())
+            }
+            ASTnode::While {
+                condition,
+                body,
+                label,
+                metadata,
+            } => {
+                self.expr(condition)?;
+                self.check_condition(condition, metadata)?;
+                self.loop_body(body, label, metadata)
+            }
+            ASTnode::Loop {
+                body,
+                label,
+                metadata,
+            } => self.loop_body(body, label, metadata),
+            ASTnode::For {
+                variable,
+                iterable,
+                step,
+                body,
+                label,
+                metadata,
+                ..
+            } => {
+                if let Some(step) = step {
+                    self.expr(step)?;
+                    if is_number_literal(step) && literal_value(step) <= 0 {
+                        return Err(CompileError::new(
+                            "range step has to be positive, use `rev` to count down".to_string(),
+                        )
+                        .attach_metadata(step.metadata().unwrap_or(metadata).clone()));
+                    }
+                }
+                self.expr(iterable)?;
+                // the parser only builds a For over a range
+                let ASTnode::Range { start, end, .. } = iterable.as_ref() else {
+                    unreachable!()
+                };
+                let (start, end) = (self.type_of(start)?, self.type_of(end)?);
+                if let (Some(start), Some(end)) = (&start, &end) {
+                    if type_name(start) != type_name(end) {
+                        return Err(CompileError::new(format!(
+                            "range bounds have different types, `{}` and `{}`",
+                            type_name(start),
+                            type_name(end)
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                }
+                // the variable takes the type of the bounds
+                let kind = start.or(end).unwrap_or_else(|| ASTnode::Type {
+                    name: DEFAULT_INTEGER_TYPE.to_string(),
+                    metadata: metadata.clone(),
+                });
+                if !is_integer(&kind) {
+                    return Err(CompileError::new(format!(
+                        "range bounds have to be integers, found `{}`",
+                        type_name(&kind)
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                if let Some(step) = step {
+                    self.check_value(&kind, step, metadata)?;
+                }
+                self.push_scope(HashMap::from([(variable.clone(), kind)]));
+                let result = self.loop_body(body, label, metadata);
+                self.pop_scope();
+                result
+            }
+            ASTnode::Asm { operands, .. } => {
+                let mut bound: Vec<String> = Vec::new();
+                for operand in operands.iter_mut() {
+                    let ASTnode::AsmOperand {
+                        direction,
+                        register,
+                        value,
+                        metadata,
+                    } = operand
+                    else {
+                        unreachable!()
+                    };
+                    if !ASM_REGISTERS.contains(&register.as_str()) {
+                        return Err(CompileError::new(format!(
+                            "`{register}` is not a register an asm block can bind, use r1 to r12"
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                    if bound.contains(register) {
+                        return Err(CompileError::new(format!(
+                            "register `{register}` is used by more than one operand"
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                    bound.push(register.clone());
+                    if let Some(value) = value {
+                        self.expr(value)?;
+                        if *direction != AsmDirection::In {
+                            self.check_assignable(value, metadata)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ASTnode::Break { label, metadata } => self.in_loop("break", label, metadata),
+            ASTnode::Continue { label, metadata } => self.in_loop("continue", label, metadata),
+            ASTnode::Match {
+                value,
+                arms,
+                jump_table,
+                metadata,
+            } => {
+                self.expr(value)?;
+                let kind = self.type_of(value)?;
+                *jump_table = self.check_match(kind.as_ref(), arms, metadata)?;
+                for arm in arms.iter_mut() {
+                    let ASTnode::MatchArm { pattern, body, .. } = arm else {
+                        unreachable!()
+                    };
+                    self.push_scope(self.pattern_bindings(pattern));
+                    let result = self.statement(body);
+                    self.pop_scope();
+                    result?;
+                }
+                Ok(())
+            }
+            ASTnode::Return { value, metadata } => {
+                if let Some(value) = value {
+                    self.expr(value)?;
+                }
+                match (&self.return_type, value) {
+                    (Some(expected), Some(value)) => self.check_value(expected, value, metadata),
+                    (Some(expected), None) if type_name(expected) != "()" => {
+                        Err(CompileError::new(format!(
+                            "`return` needs a value of type `{}`",
+                            type_name(expected)
+                        ))
+                        .attach_metadata(metadata.clone()))
+                    }
+                    (None, Some(_)) => Err(CompileError::new(
+                        "`return` with a value in a function that returns nothing".to_string(),
+                    )
+                    .attach_metadata(metadata.clone())),
+                    _ => Ok(()),
+                }
+            }
+            ASTnode::Assign {
+                dest,
+                expr,
+                metadata,
+            } => {
+                self.expr(dest)?;
+                self.expr(expr)?;
+                self.check_assignable(dest, metadata)?;
+                match self.type_of(dest)? {
+                    Some(kind) => self.check_value(&kind, expr, metadata),
+                    None => Ok(()),
+                }
+            }
+            ASTnode::CompoundAssign {
+                dest,
+                operator,
+                expr,
+                scale,
+                metadata,
+            } => {
+                self.expr(dest)?;
+                self.expr(expr)?;
+                self.check_assignable(dest, metadata)?;
+                let kind = self.type_of(dest)?;
+                match (&kind, &operator) {
+                    (_, AssignOperator::ShiftLeft | AssignOperator::ShiftRight) => {
+                        let amount = self.type_of(expr)?;
+                        if check_shift(kind.as_ref(), amount.as_ref(), expr, metadata)? {
+                            *operator = AssignOperator::ArithmeticShiftRight;
+                        }
+                    }
+                    // the offset is counted in values of the pointed to type
+                    (
+                        Some(ASTnode::PointerType { kind, .. }),
+                        AssignOperator::Add | AssignOperator::Sub,
+                    ) => {
+                        self.check_offset(expr, metadata)?;
+                        (*scale, _) = self.size_align(kind)?;
+                    }
+                    (Some(ASTnode::PointerType { .. }), _) => {
+                        return Err(CompileError::new(
+                            "only `+=` and `-=` can be applied to a pointer".to_string(),
+                        )
+                        .attach_metadata(metadata.clone()))
+                    }
+                    _ => {
+                        let (operator, floats) = match operator {
+                            AssignOperator::Add => ("+=", true),
+                            AssignOperator::Sub => ("-=", true),
+                            AssignOperator::Mult => ("*=", true),
+                            AssignOperator::Div => ("/=", true),
+                            AssignOperator::Mod => ("%=", true),
+                            AssignOperator::And => ("&=", false),
+                            AssignOperator::Or => ("|=", false),
+                            _ => ("^=", false),
+                        };
+                        self.operand_type(operator, dest, expr, floats, metadata)?;
+                    }
+                }
+                Ok(())
+            }
+            _ => self.expr(node),
+        }
+    }
+
+    /// rejects `keyword` when it is not inside of a loop or names a label no enclosing loop has
+    fn in_loop(
+        &self,
+        keyword: &str,
+        label: &Option<String>,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        if self.loops.is_empty() {
+            return Err(CompileError::new(format!("`{keyword}` outside of a loop"))
+                .attach_metadata(metadata.clone()));
+        }
+        if let Some(label) = label {
+            if !self.loops.contains(&Some(label.clone())) {
+                return Err(
+                    CompileError::new(format!("use of undeclared label `'{label}`"))
+                        .attach_metadata(metadata.clone()),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// conditions are bools, an integer has to be compared explicitly
+    fn check_condition(
+        &self,
+        condition: &ASTnode,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        match self.type_of(condition)? {
+            Some(ASTnode::Type { name, .. }) if name == "bool" => Ok(()),
+            kind => Err(CompileError::new(format!(
+                "condition must be a `bool`, found `{}`, compare it explicitly such as `!= 0`",
+                kind.as_ref()
+                    .map_or(DEFAULT_INTEGER_TYPE.to_string(), type_name)
+            ))
+            .attach_metadata(metadata.clone())),
+        }
+    }
+
+    /// operand of `!`, `&&` and `||`
+    fn check_bool(
+        &self,
+        operand: &ASTnode,
+        operator: &str,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        match self.type_of(operand)? {
+            Some(ASTnode::Type { name, .. }) if name == "bool" => Ok(()),
+            kind => Err(CompileError::new(format!(
+                "`{operator}` takes a `bool`, found `{}`{}",
+                kind.as_ref()
+                    .map_or(DEFAULT_INTEGER_TYPE.to_string(), type_name),
+                if operator == "!" {
+                    ", use `~` to flip the bits of an integer"
+                } else {
+                    ""
+                }
+            ))
+            .attach_metadata(metadata.clone())),
+        }
+    }
+
+    /// a pointer is moved by an integer number of values
+    fn check_offset(
+        &self,
+        offset: &ASTnode,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        match self.type_of(offset)? {
+            Some(kind) if !is_integer(&kind) => Err(CompileError::new(format!(
+                "a pointer is moved by an integer, found `{}`",
+                type_name(&kind)
+            ))
+            .attach_metadata(offset.metadata().unwrap_or(metadata).clone())),
+            _ => Ok(()),
+        }
+    }
+
+    /// type of `left operator right` for an arithmetic or bitwise operator. both sides have the
+    /// same type, integers or with `floats` also f32
+    fn operand_type(
+        &self,
+        operator: &str,
+        left: &ASTnode,
+        right: &ASTnode,
+        floats: bool,
+        metadata: &MetadataReference,
+    ) -> Result<Option<ASTnode>, CompileError> {
+        let kind = self.common_type(operator, left, right, metadata)?;
+        let number = |kind: &ASTnode| is_integer(kind) || (floats && is_float(kind));
+        match &kind {
+            Some(kind) if !number(kind) => Err(CompileError::new(format!(
+                "`{operator}` takes {}, found `{}`",
+                if floats {
+                    "integers or `f32`"
+                } else {
+                    "integers"
+                },
+                type_name(kind)
+            ))
+            .attach_metadata(metadata.clone())),
+            _ => Ok(kind),
+        }
+    }
+
+    /// operands of a comparison have the same type. numbers and pointers can be ordered, bools,
+    /// function pointers and enums without values can only be compared for equality
+    fn check_comparison(
+        &self,
+        operator: &str,
+        op: &BinaryOP,
+        ordered: bool,
+    ) -> Result<(), CompileError> {
+        let Some(kind) = self.common_type(operator, &op.left, &op.right, &op.metadata)? else {
+            return Ok(());
+        };
+        let comparable = match &kind {
+            ASTnode::PointerType { .. } => true,
+            ASTnode::FunctionType { .. } => !ordered,
+            ASTnode::Type { name, .. } if name == "bool" => !ordered,
+            ASTnode::Type { name, .. } => {
+                is_integer(&kind)
+                    || is_float(&kind)
+                    || (!ordered
+                        && self
+                            .enums
+                            .get(name)
+                            .is_some_and(|info| info.variants.iter().all(|v| v.fields.is_empty())))
+            }
+            _ => false,
+        };
+        if !comparable {
+            return Err(CompileError::new(format!(
+                "`{operator}` cannot compare values of type `{}`",
+                type_name(&kind)
+            ))
+            .attach_metadata(op.metadata.clone()));
+        }
+        Ok(())
+    }
+
+    /// the type both operands of a binary operator share, values are never converted
+    /// implicitly. an untyped literal takes the type of the other side and has to fit in it
+    fn common_type(
+        &self,
+        operator: &str,
+        left: &ASTnode,
+        right: &ASTnode,
+        metadata: &MetadataReference,
+    ) -> Result<Option<ASTnode>, CompileError> {
+        match (self.type_of(left)?, self.type_of(right)?) {
+            (Some(left), Some(right)) if type_name(&left) != type_name(&right) => {
+                Err(CompileError::new(format!(
+                    "`{operator}` takes two values of the same type, found `{}` and `{}`",
+                    type_name(&left),
+                    type_name(&right)
+                ))
+                .attach_metadata(metadata.clone()))
+            }
+            (Some(kind), None) => {
+                self.check_value(&kind, right, metadata)?;
+                Ok(Some(kind))
+            }
+            (None, Some(kind)) => {
+                self.check_value(&kind, left, metadata)?;
+                Ok(Some(kind))
+            }
+            (kind, _) => Ok(kind),
+        }
+    }
+
+    fn loop_body(
+        &mut self,
+        body: &mut ASTnode,
+        label: &Option<String>,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        if let Some(name) = label {
+            if self.loops.contains(label) {
+                return Err(CompileError::new(format!(
+                    "label `'{name}` shadows the label of an enclosing loop"
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+        }
+        self.loops.push(label.clone());
+        let result = self.statement(body);
+        self.loops.pop();
+        result
+    }
+
+    /// checks the count and the types of the arguments of a call against the FunctionType of the
+    /// function and gives its return type. literals have to fit their parameter, arguments past
+    /// the parameters of a variadic function only have to fit a stack slot. `callee` is how the
+    /// function is named in errors
+    fn check_arguments(
+        &self,
+        callee: &str,
+        signature: &ASTnode,
+        params: &[ASTnode],
+        metadata: &MetadataReference,
+    ) -> Result<Option<ASTnode>, CompileError> {
+        let ASTnode::FunctionType {
+            params: param_types,
+            return_type,
+            variadic,
+            ..
+        } = signature
+        else {
+            unreachable!()
+        };
+        if params.len() < param_types.len() || (!variadic && params.len() > param_types.len()) {
+            return Err(CompileError::new(format!(
+                "wrong number of arguments to {callee}, expected {}{}, found {}",
+                if *variadic { "at least " } else { "" },
+                param_types.len(),
+                params.len()
+            ))
+            .attach_metadata(metadata.clone()));
+        }
+        for param in &params[param_types.len()..] {
+            let Some(found) = self.type_of(param)? else {
+                continue;
+            };
+            if self.size_align(&found)?.0 > VARIADIC_SLOT_SIZE {
+                return Err(CompileError::new(format!(
+                    "`{}` does not fit a variadic argument slot, pass a pointer to it",
+                    type_name(&found)
+                ))
+                .attach_metadata(metadata.clone()));
+            }
+        }
+        for (param, expected) in params.iter().zip(param_types) {
+            // array and tuple literals are checked element by element, like number literals
+            let literal = matches!(
+                param,
+                ASTnode::ArrayLiteral { .. }
+                    | ASTnode::ArrayRepeat { .. }
+                    | ASTnode::TupleLiteral { .. }
+            );
+            match self.type_of(param)? {
+                Some(found) if !literal && type_name(&found) != type_name(expected) => {
+                    return Err(CompileError::new(format!(
+                        "mismatched argument to {callee}, expected `{}`, found `{}`",
+                        type_name(expected),
+                        type_name(&found)
+                    ))
+                    .attach_metadata(param.metadata().unwrap_or(metadata).clone()));
+                }
+                _ => self.check_value(expected, param, metadata)?,
+            }
+        }
+        Ok(return_type.as_deref().cloned())
+    }
+
+    /// checks the variadic arguments of a call to a `@format` function against its format string
+    /// when it is a literal. `%s` takes a `*u8`, `%d` a signed integer, `%u` and `%x` an unsigned
+    /// one and `%c` a `u8`
+    fn check_format(
+        &self,
+        format: &ASTnode,
+        args: &[ASTnode],
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        let ASTnode::StringLiteral {
+            value,
+            metadata: format_metadata,
+        } = format
+        else {
+            return Ok(());
+        };
+        let mut specifiers: Vec<char> = Vec::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                continue;
+            }
+            match chars.next() {
+                Some('%') => (),
+                Some(specifier @ ('s' | 'd' | 'u' | 'x' | 'c')) => specifiers.push(specifier),
+                Some(other) => {
+                    return Err(CompileError::new(format!(
+                        "unknown format `%{other}`, expected one of `%s`, `%d`, `%u`, `%x`, `%c` \
+                         or `%%`"
+                    ))
+                    .attach_metadata(format_metadata.clone()))
+                }
+                None => {
+                    return Err(CompileError::new(
+                        "format string ends in a lone `%`, write `%%` for a percent sign"
+                            .to_string(),
+                    )
+                    .attach_metadata(format_metadata.clone()))
+                }
+            }
+        }
+        if let Some(extra) = args.get(specifiers.len()) {
+            return Err(
+                CompileError::new("argument is not used by the format string".to_string())
+                    .attach_metadata(extra.metadata().unwrap_or(metadata).clone()),
+            );
+        }
+        if let Some(specifier) = specifiers.get(args.len()) {
+            return Err(
+                CompileError::new(format!("missing argument for `%{specifier}`"))
+                    .attach_metadata(format_metadata.clone()),
+            );
+        }
+        for (specifier, arg) in specifiers.iter().zip(args) {
+            let found = match self.type_of(arg)? {
+                Some(kind) => type_name(&kind),
+                None => DEFAULT_INTEGER_TYPE.to_string(),
+            };
+            let integer = PrimitiveType::from_name(&found).filter(|primitive| {
+                primitive.range().is_some() && *primitive != PrimitiveType::Bool
+            });
+            let (fits, expected) = match specifier {
+                's' => (found == "*u8", "a `*u8`"),
+                'd' => (integer.is_some_and(|p| p.is_signed()), "a signed integer"),
+                'u' | 'x' => (
+                    integer.is_some_and(|p| !p.is_signed()),
+                    "an unsigned integer",
+                ),
+                _ => (found == "u8", "a `u8`"),
+            };
+            // an untyped literal takes whatever integer type the format asks for
+            let literal = matches!(arg, ASTnode::NumberLiteral { .. }) && *specifier != 's';
+            if !fits && !literal {
+                return Err(CompileError::new(format!(
+                    "`%{specifier}` takes {expected}, found `{found}`"
+                ))
+                .attach_metadata(arg.metadata().unwrap_or(metadata).clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// adds a declaration to the innermost scope, inferring its type from `value` if needed
+    fn declare(
+        &mut self,
+        name: &str,
+        kind: &mut Option<Box<ASTnode>>,
+        value: Option<&ASTnode>,
+        mutable: bool,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        if let Some(kind) = kind {
+            self.check_type(kind)?;
+            if let Some(value) = value {
+                self.check_value(kind, value, metadata)?;
+            }
+        } else {
+            let inferred = match value {
+                Some(v) => self.type_of(v)?,
+                None => None,
+            };
+            *kind = Some(Box::new(inferred.unwrap_or_else(|| ASTnode::Type {
+                name: DEFAULT_INTEGER_TYPE.to_string(),
+                metadata: metadata.clone(),
+            })));
+        }
+        let kind = kind.as_deref().unwrap().clone();
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), Binding { kind, mutable });
+        Ok(())
+    }
+
+    /// rejects writing to `dest` when it is not a memory location, or when it is a const or a
+    /// static without `mut` or part of one
+    fn check_assignable(
+        &self,
+        dest: &ASTnode,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        check_lvalue(dest, metadata)?;
+        // a field or element is inside of the variable holding it, an element behind a pointer
+        // is not
+        let mut root = dest;
+        loop {
+            root = match root {
+                ASTnode::FieldAccess { value, .. } => value,
+                ASTnode::Index { value, .. }
+                    if matches!(self.type_of(value)?, Some(ASTnode::ArrayType { .. })) =>
+                {
+                    value
+                }
+                _ => break,
+            };
+        }
+        match root {
+            ASTnode::Identifier { name, .. }
+                if self.binding(name).is_some_and(|binding| !binding.mutable) =>
+            {
+                Err(CompileError::new(format!(
+                    "cannot assign to `{name}`, only variables and `static mut` can be assigned"
+                ))
+                .attach_metadata(metadata.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// checks that `value` is a `expected`, values are never converted implicitly. number
+    /// literals take the type they are given to and have to fit in it
+    fn check_value(
+        &self,
+        expected: &ASTnode,
+        value: &ASTnode,
+        metadata: &MetadataReference,
+    ) -> Result<(), CompileError> {
+        let metadata = value.metadata().unwrap_or(metadata);
+        match (expected, value) {
+            (ASTnode::ArrayType { kind, length, .. }, ASTnode::ArrayLiteral { elements, .. }) => {
+                if elements.len() != *length {
+                    return Err(CompileError::new(format!(
+                        "expected an array of {length} elements, found {}",
+                        elements.len()
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                elements
+                    .iter()
+                    .try_for_each(|element| self.check_value(kind, element, metadata))
+            }
+            (
+                ASTnode::ArrayType { kind, length, .. },
+                ASTnode::ArrayRepeat {
+                    value,
+                    length: found,
+                    ..
+                },
+            ) => {
+                if found != length {
+                    return Err(CompileError::new(format!(
+                        "expected an array of {length} elements, found {found}"
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                self.check_value(kind, value, metadata)
+            }
+            (
+                ASTnode::TupleType {
+                    elements: kinds, ..
+                },
+                ASTnode::TupleLiteral { elements, .. },
+            ) => {
+                if elements.len() != kinds.len() {
+                    return Err(CompileError::new(format!(
+                        "expected a tuple of {} elements, found {}",
+                        kinds.len(),
+                        elements.len()
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                kinds
+                    .iter()
+                    .zip(elements)
+                    .try_for_each(|(kind, element)| self.check_value(kind, element, metadata))
+            }
+            _ if is_number_literal(value) => {
+                let range = match expected {
+                    ASTnode::Type { name, .. } => match PrimitiveType::from_name(name) {
+                        // a bool is only made by comparing
+                        Some(PrimitiveType::Bool) | None => {
+                            return Err(CompileError::new(format!(
+                                "expected `{name}`, found a number"
+                            ))
+                            .attach_metadata(metadata.clone()))
+                        }
+                        Some(primitive) => primitive.range(),
+                    },
+                    // an address
+                    ASTnode::PointerType { .. } => Some((0, u64::MAX as i128)),
+                    _ => {
+                        return Err(CompileError::new(format!(
+                            "expected `{}`, found a number",
+                            type_name(expected)
+                        ))
+                        .attach_metadata(metadata.clone()))
+                    }
+                };
+                let literal = literal_value(value);
+                match range {
+                    Some((low, high)) if literal < low || literal > high => Err(CompileError::new(
+                        format!("`{literal}` does not fit in `{}`", type_name(expected)),
+                    )
+                    .attach_metadata(metadata.clone())),
+                    _ => Ok(()),
+                }
+            }
+            _ => match self.type_of(value)? {
+                Some(found) if type_name(&found) != type_name(expected) => {
+                    Err(CompileError::new(format!(
+                        "expected `{}`, found `{}`",
+                        type_name(expected),
+                        type_name(&found)
+                    ))
+                    .attach_metadata(metadata.clone()))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
+    /// value.method(params) as a call to the function `Type$method` of the impl of the type of
+    /// `value`, which is passed as the first argument, referenced or dereferenced to match it.
+    /// None when `value` has a field of that name, which is called through
+    fn method_call(
+        &mut self,
+        callee: &mut ASTnode,
+        params: &mut Vec<ASTnode>,
+        metadata: &MetadataReference,
+    ) -> Result<Option<ASTnode>, CompileError> {
+        let ASTnode::FieldAccess { value, field, .. } = callee else {
+            unreachable!()
+        };
+        self.expr(value)?;
+        let (kind, pointer) = match self.type_of(value)? {
+            Some(ASTnode::Type { name, .. }) => (name, false),
+            Some(ASTnode::PointerType { kind, .. }) => match *kind {
+                ASTnode::Type { name, .. } => (name, true),
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let is_field = self
+            .structs
+            .get(&kind)
+            .is_some_and(|layout| layout.fields.iter().any(|(f, _, _)| f == field));
+        let label = kind.clone() + MANGLE_SEPARATOR + field;
+        let Some(ASTnode::FunctionType {
+            params: param_types,
+            ..
+        }) = self.functions.get(&label).filter(|_| !is_field)
+        else {
+            return Ok(None);
+        };
+        let value = std::mem::replace(
+            value.as_mut(),
+            ASTnode::Wildcard {
+                metadata: metadata.clone(),
+            },
+        );
+        let receiver = match param_types.first() {
+            Some(ASTnode::Type { name, .. }) if *name == kind && pointer => ASTnode::Deref {
+                value: Box::new(value),
+                metadata: metadata.clone(),
+            },
+            Some(ASTnode::Type { name, .. }) if *name == kind => value,
+            Some(ASTnode::PointerType { kind: pointee, .. }) if matches!(pointee.as_ref(), ASTnode::Type { name, .. } if *name == kind) => {
+                if pointer {
+                    value
+                } else {
+                    ASTnode::AddressOf {
+                        value: Box::new(value),
+                        metadata: metadata.clone(),
+                    }
+                }
+            }
+            _ => {
+                return Err(CompileError::new(format!(
+                    "`{field}` has no `self` parameter of type `{kind}`, call it as \
+                     `{}::{field}(..)`",
+                    kind.replace(MANGLE_SEPARATOR, "::")
+                ))
+                .attach_metadata(metadata.clone()))
+            }
+        };
+        params.insert(0, receiver);
+        Ok(Some(ASTnode::FunctionCall {
+            name: label,
+            params: std::mem::take(params),
+            metadata: metadata.clone(),
+        }))
+    }
+
+    fn lookup(&self, name: &str) -> Option<&ASTnode> {
+        self.binding(name).map(|binding| &binding.kind)
+    }
+
+    fn binding(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// checks that every name used in `node` resolves, marks variables whose address is taken
+    /// and rewrites pointer arithmetic into PointerOffset
+    fn expr(&mut self, node: &mut ASTnode) -> Result<(), CompileError> {
+        // a function's address is its label, a variable holding a function pointer is called
+        // through it, variables shadow functions
+        match node {
+            ASTnode::AddressOf { value, metadata } => {
+                if let ASTnode::Identifier { name, .. } = value.as_ref() {
+                    if self.lookup(name).is_none() && self.functions.contains_key(name) {
+                        *node = ASTnode::FunctionAddress {
+                            name: name.clone(),
+                            metadata: metadata.clone(),
+                        };
+                    }
+                }
+            }
+            ASTnode::FunctionCall {
+                name,
+                params,
+                metadata,
+            } if self.lookup(name).is_some() => {
+                let callee = ASTnode::Identifier {
+                    name: name.clone(),
+                    metadata: metadata.clone(),
+                };
+                *node = ASTnode::Call {
+                    callee: Box::new(callee),
+                    params: std::mem::take(params),
+                    metadata: metadata.clone(),
+                };
+            }
+            ASTnode::Call {
+                callee,
+                params,
+                metadata,
+            } if matches!(**callee, ASTnode::FieldAccess { .. }) => {
+                if let Some(call) = self.method_call(callee, params, metadata)? {
+                    *node = call;
+                }
+            }
+            _ => (),
+        }
+        let children = match node {
+            // the variant being constructed is not a value on its own
+            ASTnode::Call { callee, params, .. } if matches!(**callee, ASTnode::Path { .. }) => {
+                params.iter_mut().collect()
+            }
+            _ => node.children_mut(),
+        };
+        for child in children {
+            self.expr(child)?;
+        }
+        let negative = matches!(node, ASTnode::Sub(_));
+        match node {
+            ASTnode::AddressOf { value, metadata } => {
+                check_lvalue(value, metadata)?;
+                // the address of a field or element is inside of the variable holding it,
+                // an element behind a pointer is not
+                let mut root = value.as_ref();
+                loop {
+                    root = match root {
+                        ASTnode::FieldAccess { value, .. } => value,
+                        ASTnode::Index { value, .. }
+                            if matches!(self.type_of(value)?, Some(ASTnode::ArrayType { .. })) =>
+                        {
+                            value
+                        }
+                        _ => break,
+                    };
+                }
+                if let ASTnode::Identifier { name, .. } = root {
+                    if let Some(i) = self.scopes.iter().rposition(|s| s.contains_key(name)) {
+                        self.addressed[i].insert(name.clone());
+                    }
+                }
+            }
+            ASTnode::Add(op) | ASTnode::Sub(op) => {
+                let left = self.type_of(&op.left)?;
+                let right = self.type_of(&op.right)?;
+                let (pointer, offset, kind) = match (&left, &right) {
+                    (Some(ASTnode::PointerType { .. }), Some(ASTnode::PointerType { .. })) => {
+                        return Err(CompileError::new(
+                            "arithmetic between two pointers is not supported".to_string(),
+                        )
+                        .attach_metadata(op.metadata.clone()))
+                    }
+                    (Some(ASTnode::PointerType { kind, .. }), _) => (&op.left, &op.right, kind),
+                    (_, Some(ASTnode::PointerType { kind, .. })) if !negative => {
+                        (&op.right, &op.left, kind)
+                    }
+                    (_, Some(ASTnode::PointerType { .. })) => {
+                        return Err(CompileError::new(
+                            "a pointer can not be subtracted from a value".to_string(),
+                        )
+                        .attach_metadata(op.metadata.clone()))
+                    }
+                    _ => return self.type_of(node).map(|_| ()),
+                };
+                self.check_offset(offset, &op.metadata)?;
+                let (scale, _) = self.size_align(kind)?;
+                let offset = ASTnode::PointerOffset {
+                    pointer: pointer.clone(),
+                    offset: offset.clone(),
+                    scale,
+                    negative,
+                    metadata: op.metadata.clone(),
+                };
+                *node = offset;
+            }
+            ASTnode::FunctionCall {
+                name,
+                params,
+                metadata,
+            } if self.formats.contains(name.as_str()) => {
+                let ASTnode::FunctionType {
+                    params: param_types,
+                    ..
+                } = &self.functions[name.as_str()]
+                else {
+                    unreachable!()
+                };
+                // a wrong number of arguments is reported by type_of
+                if params.len() >= param_types.len() {
+                    let (fixed, variadic) = params.split_at(param_types.len());
+                    self.check_format(fixed.last().unwrap(), variadic, metadata)?;
+                }
+            }
+            ASTnode::VaStart { metadata } if !self.variadic => {
+                return Err(CompileError::new(
+                    "`va_start` can only be used in a function taking `...`".to_string(),
+                )
+                .attach_metadata(metadata.clone()))
+            }
+            ASTnode::VaArg {
+                list,
+                kind,
+                metadata,
+            } => {
+                self.check_assignable(list, metadata)?;
+                let list_kind = self.type_of(list)?;
+                if !matches!(&list_kind, Some(ASTnode::PointerType { kind, .. })
+                    if matches!(kind.as_ref(), ASTnode::Type { name, .. } if name == "u64"))
+                {
+                    return Err(CompileError::new(
+                        "`va_arg` takes the `*u64` given by `va_start`".to_string(),
+                    )
+                    .attach_metadata(metadata.clone()));
+                }
+                self.check_type(kind)?;
+                if self.size_align(kind)?.0 > VARIADIC_SLOT_SIZE {
+                    return Err(CompileError::new(format!(
+                        "`{}` does not fit a variadic argument slot",
+                        type_name(kind)
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+            }
+            // layout is known after type declarations, these are constants from here on
+            ASTnode::SizeOf { kind, metadata } => {
+                self.check_type(kind)?;
+                let (size, _) = self.size_align(kind)?;
+                let size = ASTnode::NumberLiteral {
+                    value: size,
+                    metadata: metadata.clone(),
+                };
+                *node = size;
+            }
+            ASTnode::AlignOf { kind, metadata } => {
+                self.check_type(kind)?;
+                let (_, align) = self.size_align(kind)?;
+                let align = ASTnode::NumberLiteral {
+                    value: align,
+                    metadata: metadata.clone(),
+                };
+                *node = align;
+            }
+            ASTnode::OffsetOf {
+                kind,
+                fields,
+                metadata,
+            } => {
+                self.check_type(kind)?;
+                let (mut offset, mut kind) = (0, *kind.clone());
+                for field in fields.iter() {
+                    let (field_offset, field_kind) = self.field_offset(&kind, field, metadata)?;
+                    offset += field_offset;
+                    kind = field_kind;
+                }
+                let offset = ASTnode::NumberLiteral {
+                    value: offset,
+                    metadata: metadata.clone(),
+                };
+                *node = offset;
+            }
+            ASTnode::Cast {
+                value,
+                kind,
+                conversion,
+                metadata,
+            } => {
+                self.check_type(kind)?;
+                let from = self.type_of(value)?.unwrap_or_else(|| ASTnode::Type {
+                    name: DEFAULT_INTEGER_TYPE.to_string(),
+                    metadata: metadata.clone(),
+                });
+                let Some(converted) = self.conversion(&from, kind) else {
+                    return Err(CompileError::new(format!(
+                        "cannot cast a value of type `{}` as `{}`",
+                        type_name(&from),
+                        type_name(kind)
+                    ))
+                    .attach_metadata(metadata.clone()));
+                };
+                *conversion = converted;
+            }
+            ASTnode::Not { value, metadata } => self.check_bool(value, "!", metadata)?,
+            ASTnode::LogicalAnd(op) => {
+                self.check_bool(&op.left, "&&", &op.metadata)?;
+                self.check_bool(&op.right, "&&", &op.metadata)?;
+            }
+            ASTnode::LogicalOr(op) => {
+                self.check_bool(&op.left, "||", &op.metadata)?;
+                self.check_bool(&op.right, "||", &op.metadata)?;
+            }
+            ASTnode::ShiftLeft(op) => {
+                let (kind, amount) = (self.type_of(&op.left)?, self.type_of(&op.right)?);
+                check_shift(kind.as_ref(), amount.as_ref(), &op.right, &op.metadata)?;
+            }
+            ASTnode::ShiftRight(op) => {
+                let (kind, amount) = (self.type_of(&op.left)?, self.type_of(&op.right)?);
+                let arithmetic =
+                    check_shift(kind.as_ref(), amount.as_ref(), &op.right, &op.metadata)?;
+                if arithmetic {
+                    let shift = ASTnode::ArithmeticShiftRight(op.clone());
+                    *node = shift;
+                }
+            }
+            ASTnode::Index {
+                value,
+                index,
+                checked,
+                metadata,
+            } => {
+                if let Some(ASTnode::ArrayType { length, .. }) = self.type_of(value)? {
+                    match index.as_ref() {
+                        ASTnode::NumberLiteral { value, .. } if *value >= length => {
+                            return Err(CompileError::new(format!(
+                                "index {value} is out of bounds for an array of length {length}"
+                            ))
+                            .attach_metadata(metadata.clone()))
+                        }
+                        ASTnode::NumberLiteral { .. } => (),
+                        _ => *checked = self.bounds_check,
+                    }
+                }
+            }
+            _ => (),
+        }
+        self.type_of(node).map(|_| ())
+    }
+
+    /// how a value of type `from` is converted to `to`, None when there is no such cast.
+    /// integers, f32 and pointers convert between each other, except pointers and f32,
+    /// enums without values convert to integers through their tag
+    fn conversion(&self, from: &ASTnode, to: &ASTnode) -> Option<Conversion> {
+        let from_pointer = matches!(from, ASTnode::PointerType { .. });
+        let to_pointer = matches!(to, ASTnode::PointerType { .. });
+        let from = match from {
+            ASTnode::PointerType { .. } => PrimitiveType::Unsigned64,
+            ASTnode::Type { name, .. } => match PrimitiveType::from_name(name) {
+                Some(primitive) => primitive,
+                None => {
+                    let info = self.enums.get(name)?;
+                    if info.variants.iter().any(|v| !v.fields.is_empty()) {
+                        return None;
+                    }
+                    info.kind.clone()
+                }
+            },
+            _ => return None,
+        };
+        let to = match to {
+            ASTnode::PointerType { .. } => PrimitiveType::Unsigned64,
+            ASTnode::Type { name, .. } => PrimitiveType::from_name(name)?,
+            _ => return None,
+        };
+        // a bool is only made by comparing
+        if to == PrimitiveType::Bool {
+            return None;
+        }
+        let from_float = from == PrimitiveType::Float32;
+        let to_float = to == PrimitiveType::Float32;
+        if (from_pointer && to_float) || (from_float && to_pointer) {
+            return None;
+        }
+        Some(if from_float && to_float {
+            Conversion::Keep
+        } else if from_float {
+            Conversion::FloatToInt {
+                signed: to.is_signed(),
+            }
+        } else if to_float {
+            Conversion::IntToFloat {
+                signed: from.is_signed(),
+            }
+        } else if to.size() > from.size() && from.is_signed() {
+            Conversion::SignExtend
+        } else if to.size() > from.size() {
+            Conversion::ZeroExtend
+        } else if to.size() < from.size() {
+            Conversion::Truncate
+        } else {
+            Conversion::Keep
+        })
+    }
+
+    /// type of an expression, None when it has no type of its own (an untyped literal)
+    fn type_of(&self, node: &ASTnode) -> Result<Option<ASTnode>, CompileError> {
+        Ok(match node {
+            ASTnode::Identifier { name, metadata } => match self.lookup(name) {
+                Some(kind) => Some(kind.clone()),
+                None if self.functions.contains_key(name) => {
+                    return Err(CompileError::new(format!(
+                        "`{name}` is a function, take its address with `&{name}`"
+                    ))
+                    .attach_metadata(metadata.clone()))
+                }
+                None => {
+                    return Err(
+                        CompileError::new(format!("cannot find `{name}` in this scope"))
+                            .attach_metadata(metadata.clone()),
+                    )
+                }
+            },
+            ASTnode::FunctionCall {
+                name,
+                params,
+                metadata,
+            } => match self.functions.get(name) {
+                Some(signature) => {
+                    self.check_arguments(&format!("`{name}`"), signature, params, metadata)?
+                }
+                None => {
+                    return Err(CompileError::new(format!(
+                        "cannot find function `{name}` in this scope"
+                    ))
+                    .attach_metadata(metadata.clone()))
+                }
+            },
+            ASTnode::NumberLiteral { .. } => None,
+            // placed in the data section, the value is its address
+            ASTnode::StringLiteral { metadata, .. } => Some(ASTnode::PointerType {
+                kind: Box::new(ASTnode::Type {
+                    name: "u8".to_string(),
+                    metadata: metadata.clone(),
+                }),
+                metadata: metadata.clone(),
+            }),
+            ASTnode::CharLiteral { metadata, .. } => Some(ASTnode::Type {
+                name: "u8".to_string(),
+                metadata: metadata.clone(),
+            }),
+            ASTnode::BoolLiteral { metadata, .. } => Some(ASTnode::Type {
+                name: "bool".to_string(),
+                metadata: metadata.clone(),
+            }),
+            ASTnode::ArrayLiteral { elements, metadata } => {
+                let mut kind: Option<ASTnode> = None;
+                for element in elements {
+                    let element_kind = self.type_of(element)?;
+                    if let (
+                        Some(ASTnode::ArrayType { length, .. }),
+                        Some(ASTnode::ArrayType { length: found, .. }),
+                    ) = (&kind, &element_kind)
+                    {
+                        if length != found {
+                            return Err(CompileError::new(format!(
+                                "array elements have different lengths, {length} and {found}"
+                            ))
+                            .attach_metadata(metadata.clone()));
+                        }
+                    }
+                    kind = kind.or(element_kind);
+                }
+                Some(ASTnode::ArrayType {
+                    kind: Box::new(kind.unwrap_or_else(|| ASTnode::Type {
+                        name: DEFAULT_INTEGER_TYPE.to_string(),
+                        metadata: metadata.clone(),
+                    })),
+                    length: elements.len(),
+                    metadata: metadata.clone(),
+                })
+            }
+            ASTnode::ArrayRepeat {
+                value,
+                length,
+                metadata,
+            } => Some(ASTnode::ArrayType {
+                kind: Box::new(self.type_of(value)?.unwrap_or_else(|| ASTnode::Type {
+                    name: DEFAULT_INTEGER_TYPE.to_string(),
+                    metadata: metadata.clone(),
+                })),
+                length: *length,
+                metadata: metadata.clone(),
+            }),
+            ASTnode::TupleLiteral { elements, metadata } => {
+                let mut kinds: Vec<ASTnode> = Vec::new();
+                for element in elements {
+                    kinds.push(self.type_of(element)?.unwrap_or_else(|| ASTnode::Type {
+                        name: DEFAULT_INTEGER_TYPE.to_string(),
+                        metadata: metadata.clone(),
+                    }));
+                }
+                Some(ASTnode::TupleType {
+                    elements: kinds,
+                    metadata: metadata.clone(),
+                })
+            }
+            ASTnode::Not { value, .. } => self.type_of(value)?,
+            // `~` flips the bits of an integer, `!` is the logical not of a bool
+            ASTnode::Negate { value, metadata } | ASTnode::BitNot { value, metadata } => {
+                let negate = matches!(node, ASTnode::Negate { .. });
+                match self.type_of(value)? {
+                    Some(kind) if !(is_integer(&kind) || (negate && is_float(&kind))) => {
+                        return Err(CompileError::new(format!(
+                            "`{}` takes {}, found `{}`",
+                            if negate { "-" } else { "~" },
+                            if negate {
+                                "an integer or `f32`"
+                            } else {
+                                "an integer"
+                            },
+                            type_name(&kind)
+                        ))
+                        .attach_metadata(metadata.clone()))
+                    }
+                    kind => kind,
+                }
+            }
+            ASTnode::AddressOf { value, metadata } => {
+                let kind = self.type_of(value)?.unwrap_or_else(|| ASTnode::Type {
+                    name: DEFAULT_INTEGER_TYPE.to_string(),
+                    metadata: metadata.clone(),
+                });
+                Some(ASTnode::PointerType {
+                    kind: Box::new(kind),
+                    metadata: metadata.clone(),
+                })
+            }
+            ASTnode::Deref { value, metadata } => match self.type_of(value)? {
+                Some(ASTnode::PointerType { kind, .. }) => Some(*kind),
+                kind => {
+                    return Err(CompileError::new(format!(
+                        "cannot dereference a value of type `{}`, it is not a pointer",
+                        kind.as_ref()
+                            .map_or(DEFAULT_INTEGER_TYPE.to_string(), type_name)
+                    ))
+                    .attach_metadata(metadata.clone()))
+                }
+            },
+            ASTnode::PointerOffset { pointer, .. } => self.type_of(pointer)?,
+            ASTnode::Cast { value, kind, .. } => {
+                self.type_of(value)?;
+                Some(*kind.clone())
+            }
+            // arrays and pointers can both be indexed, only arrays have a known length
+            ASTnode::Index {
+                value,
+                index,
+                metadata,
+                ..
+            } => {
+                if let Some(kind) = self.type_of(index)?.filter(|kind| !is_integer(kind)) {
+                    return Err(CompileError::new(format!(
+                        "an index has to be an integer, found `{}`",
+                        type_name(&kind)
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                match self.type_of(value)? {
+                    Some(ASTnode::ArrayType { kind, .. } | ASTnode::PointerType { kind, .. }) => {
+                        Some(*kind)
+                    }
+                    kind => {
+                        return Err(CompileError::new(format!(
+                            "cannot index into a value of type `{}`",
+                            kind.as_ref()
+                                .map_or(DEFAULT_INTEGER_TYPE.to_string(), type_name)
+                        ))
+                        .attach_metadata(metadata.clone()))
+                    }
+                }
+            }
+            ASTnode::Path { segments, metadata } => {
+                let (enum_name, variant) = self.find_variant(segments, metadata)?;
+                if !variant.fields.is_empty() {
+                    return Err(CompileError::new(format!(
+                        "`{}` holds {} values, they have to be given as `{}(...)`",
+                        segments.join("::"),
+                        variant.fields.len(),
+                        segments.join("::")
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                Some(ASTnode::Type {
+                    name: enum_name.to_string(),
+                    metadata: metadata.clone(),
+                })
+            }
+            ASTnode::FunctionAddress { name, .. } => Some(self.functions[name].clone()),
+            ASTnode::VaStart { metadata } => Some(ASTnode::PointerType {
+                kind: Box::new(ASTnode::Type {
+                    name: "u64".to_string(),
+                    metadata: metadata.clone(),
+                }),
+                metadata: metadata.clone(),
+            }),
+            ASTnode::VaArg { kind, .. } => Some(kind.as_ref().clone()),
+            ASTnode::Call {
+                callee,
+                params,
+                metadata,
+            } if !matches!(callee.as_ref(), ASTnode::Path { .. }) => {
+                let Some(signature @ ASTnode::FunctionType { .. }) = self.type_of(callee)? else {
+                    return Err(CompileError::new("expression is not callable".to_string())
+                        .attach_metadata(metadata.clone()));
+                };
+                let callee = format!("`{}`", type_name(&signature));
+                self.check_arguments(&callee, &signature, params, metadata)?
+            }
+            ASTnode::Call {
+                callee,
+                params,
+                metadata,
+            } => {
+                for param in params.iter() {
+                    self.type_of(param)?;
+                }
+                let ASTnode::Path { segments, .. } = callee.as_ref() else {
+                    unreachable!()
+                };
+                let (enum_name, variant) = self.find_variant(segments, metadata)?;
+                if variant.fields.len() != params.len() {
+                    return Err(CompileError::new(format!(
+                        "`{}` holds {} values but {} were given",
+                        segments.join("::"),
+                        variant.fields.len(),
+                        params.len()
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                for (param, kind) in params.iter().zip(&variant.fields) {
+                    self.check_value(kind, param, metadata)?;
+                }
+                Some(ASTnode::Type {
+                    name: enum_name.to_string(),
+                    metadata: metadata.clone(),
+                })
+            }
+            ASTnode::StructLiteral {
+                name,
+                fields,
+                metadata,
+            } => {
+                let Some(layout) = self.structs.get(name) else {
+                    return Err(CompileError::new(format!("cannot find struct `{name}`"))
+                        .attach_metadata(metadata.clone()));
+                };
+                let mut given: Vec<&String> = Vec::new();
+                for field in fields {
+                    let ASTnode::FieldValue {
+                        name: field_name,
+                        value,
+                        metadata,
+                    } = field
+                    else {
+                        unreachable!()
+                    };
+                    let Some((_, kind, _)) = layout.fields.iter().find(|(f, _, _)| f == field_name)
+                    else {
+                        return Err(CompileError::new(format!(
+                            "struct `{name}` has no field named `{field_name}`"
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    };
+                    if given.contains(&field_name) {
+                        return Err(CompileError::new(format!(
+                            "field `{field_name}` specified more than once"
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                    given.push(field_name);
+                    self.check_value(kind, value, metadata)?;
+                }
+                let missing: Vec<&str> = layout
+                    .fields
+                    .iter()
+                    .filter(|(f, _, _)| !given.contains(&f))
+                    .map(|(f, _, _)| f.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(CompileError::new(format!(
+                        "missing fields in initializer of `{name}`: {}",
+                        missing.join(", ")
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+                Some(ASTnode::Type {
+                    name: name.clone(),
+                    metadata: metadata.clone(),
+                })
+            }
+            ASTnode::FieldAccess {
+                value,
+                field,
+                metadata,
+            } => {
+                let kind = self.type_of(value)?;
+                if let Some(ASTnode::TupleType { elements, .. }) = &kind {
+                    return match field.parse::<usize>().ok().and_then(|i| elements.get(i)) {
+                        Some(element) => Ok(Some(element.clone())),
+                        None => Err(CompileError::new(format!(
+                            "tuple of {} elements has no field `{field}`",
+                            elements.len()
+                        ))
+                        .attach_metadata(metadata.clone())),
+                    };
+                }
+                let layout = match &kind {
+                    Some(ASTnode::Type { name, .. }) => self.structs.get(name),
+                    _ => None,
+                };
+                let Some(layout) = layout else {
+                    return Err(CompileError::new(format!(
+                        "cannot access field `{field}` of a value that is not a struct"
+                    ))
+                    .attach_metadata(metadata.clone()));
+                };
+                match layout.fields.iter().find(|(f, _, _)| f == field) {
+                    Some((_, kind, _)) => Some(kind.clone()),
+                    None => {
+                        let Some(ASTnode::Type { name, .. }) = kind else {
+                            unreachable!()
+                        };
+                        return Err(CompileError::new(format!(
+                            "struct `{name}` has no field named `{field}`"
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                }
+            }
+            // a range is typed by its bounds
+            ASTnode::Range { start, end, .. } => {
+                let start = self.type_of(start)?;
+                let end = self.type_of(end)?;
+                start.or(end)
+            }
+            ASTnode::Add(op)
+            | ASTnode::Sub(op)
+            | ASTnode::Mult(op)
+            | ASTnode::Div(op)
+            | ASTnode::Mod(op)
+            | ASTnode::And(op)
+            | ASTnode::Or(op)
+            | ASTnode::Xor(op) => {
+                let (operator, floats) = match node {
+                    ASTnode::Add(_) => ("+", true),
+                    ASTnode::Sub(_) => ("-", true),
+                    ASTnode::Mult(_) => ("*", true),
+                    ASTnode::Div(_) => ("/", true),
+                    ASTnode::Mod(_) => ("%", true),
+                    ASTnode::And(_) => ("&", false),
+                    ASTnode::Or(_) => ("|", false),
+                    _ => ("^", false),
+                };
+                self.operand_type(operator, &op.left, &op.right, floats, &op.metadata)?
+            }
+            ASTnode::Equal(op)
+            | ASTnode::NotEqual(op)
+            | ASTnode::Lesser(op)
+            | ASTnode::Greater(op)
+            | ASTnode::LesserEq(op)
+            | ASTnode::GreaterEq(op) => {
+                let (operator, ordered) = match node {
+                    ASTnode::Equal(_) => ("==", false),
+                    ASTnode::NotEqual(_) => ("!=", false),
+                    ASTnode::Lesser(_) => ("<", true),
+                    ASTnode::Greater(_) => (">", true),
+                    ASTnode::LesserEq(_) => ("<=", true),
+                    _ => (">=", true),
+                };
+                self.check_comparison(operator, op, ordered)?;
+                Some(ASTnode::Type {
+                    name: "bool".to_string(),
+                    metadata: op.metadata.clone(),
+                })
+            }
+            ASTnode::LogicalAnd(op) | ASTnode::LogicalOr(op) => {
+                self.type_of(&op.left)?;
+                self.type_of(&op.right)?;
+                Some(ASTnode::Type {
+                    name: "bool".to_string(),
+                    metadata: op.metadata.clone(),
+                })
+            }
+            // the amount does not change the type of the value being shifted
+            ASTnode::ShiftLeft(op)
+            | ASTnode::ShiftRight(op)
+            | ASTnode::ArithmeticShiftRight(op) => {
+                self.type_of(&op.right)?;
+                self.type_of(&op.left)?
+            }
+            _ => None,
+        })
+    }
+
+    /// resolves `Enum::Variant`
+    fn find_variant<'a>(
+        &'a self,
+        segments: &'a [String],
+        metadata: &MetadataReference,
+    ) -> Result<(&'a str, &'a VariantInfo), CompileError> {
+        if let [enum_name, variant] = segments {
+            if let Some(info) = self.enums.get(enum_name) {
+                if let Some(variant) = info.variants.iter().find(|v| &v.name == variant) {
+                    return Ok((enum_name, variant));
+                }
+            }
+        }
+        Err(CompileError::new(format!(
+            "cannot find `{}` in this scope",
+            segments.join("::")
+        ))
+        .attach_metadata(metadata.clone()))
+    }
+
+    /// names bound by a pattern with the types of the payload values they bind
+    fn pattern_bindings(&self, pattern: &ASTnode) -> HashMap<String, ASTnode> {
+        let mut scope = HashMap::new();
+        if let ASTnode::VariantPattern {
+            segments,
+            bindings,
+            metadata,
+        } = pattern
+        {
+            if let Ok((_, variant)) = self.find_variant(segments, metadata) {
+                for (binding, kind) in bindings.iter().zip(&variant.fields) {
+                    if let ASTnode::Identifier { name, .. } = binding {
+                        scope.insert(name.clone(), kind.clone());
+                    }
+                }
+            }
+        }
+        scope
+    }
+
+    /// rejects unreachable and overlapping arms and matches that are not exhaustive.
+    /// returns whether the arms are dense enough to dispatch through a jump table
+    fn check_match(
+        &self,
+        kind: Option<&ASTnode>,
+        arms: &[ASTnode],
+        metadata: &MetadataReference,
+    ) -> Result<bool, CompileError> {
+        let kind_name = match kind {
+            Some(ASTnode::Type { name, .. }) => Some(name.as_str()),
+            _ => None,
+        };
+        let range = kind_name
+            .and_then(PrimitiveType::from_name)
+            .and_then(|p| p.range());
+        let enum_info = kind_name.and_then(|name| self.enums.get(name).map(|info| (name, info)));
+        let mut covered: Vec<(i128, i128, &MetadataReference)> = Vec::new();
+        let mut wildcard = false;
+        for arm in arms {
+            let ASTnode::MatchArm {
+                pattern,
+                metadata: arm_metadata,
+                ..
+            } = arm
+            else {
+                unreachable!()
+            };
+            if wildcard {
+                return Err(CompileError::new(
+                    "unreachable match arm, it follows a `_` arm".to_string(),
+                )
+                .attach_metadata(arm_metadata.clone()));
+            }
+            let (low, high) = match (pattern.as_ref(), enum_info) {
+                (ASTnode::Wildcard { .. }, _) => {
+                    wildcard = true;
+                    continue;
+                }
+                (
+                    ASTnode::VariantPattern {
+                        segments,
+                        bindings,
+                        metadata,
+                    },
+                    Some((enum_name, _)),
+                ) if segments.first().is_some_and(|s| s == enum_name) => {
+                    let (_, variant) = self.find_variant(segments, metadata)?;
+                    if bindings.len() != variant.fields.len() {
+                        return Err(CompileError::new(format!(
+                            "`{}` holds {} values but the pattern binds {}",
+                            segments.join("::"),
+                            variant.fields.len(),
+                            bindings.len()
+                        ))
+                        .attach_metadata(metadata.clone()));
+                    }
+                    (variant.discriminant, variant.discriminant)
+                }
+                (_, Some((enum_name, _))) => {
+                    return Err(
+                        CompileError::new(format!("expected a variant of `{enum_name}`"))
+                            .attach_metadata(arm_metadata.clone()),
+                    );
+                }
+                (ASTnode::VariantPattern { .. }, None) => {
+                    return Err(CompileError::new(
+                        "expected an integer or character pattern, the matched value is not an enum"
+                            .to_string(),
+                    )
+                    .attach_metadata(arm_metadata.clone()));
+                }
+                (pattern, None) => pattern_bounds(pattern),
+            };
+            if low > high {
+                return Err(CompileError::new("empty range pattern".to_string())
+                    .attach_metadata(arm_metadata.clone()));
+            }
+            if let (Some((min, max)), Some(name)) = (range, kind_name) {
+                if low < min || high > max {
+                    return Err(
+                        CompileError::new(format!("pattern out of range for `{name}`"))
+                            .attach_metadata(arm_metadata.clone()),
+                    );
+                }
+            }
+            if let Some((_, _, other)) = covered.iter().find(|(l, h, _)| low <= *h && *l <= high) {
+                return Err(CompileError::new(format!(
+                    "match arm overlaps with the arm at {other:?}"
+                ))
+                .attach_metadata(arm_metadata.clone()));
+            }
+            covered.push((low, high, arm_metadata));
+        }
+        covered.sort_by_key(|(low, _, _)| *low);
+        if !wildcard {
+            if let Some((enum_name, info)) = enum_info {
+                let missing: Vec<String> = info
+                    .variants
+                    .iter()
+                    .filter(|v| !covered.iter().any(|(low, _, _)| *low == v.discriminant))
+                    .map(|v| format!("{enum_name}::{}", v.name))
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(CompileError::new(format!(
+                        "non-exhaustive match, missing {}",
+                        missing.join(", ")
+                    ))
+                    .attach_metadata(metadata.clone()));
+                }
+            } else {
+                // without `_` the arms have to cover every value of the type
+                let exhaustive = range.is_some_and(|(min, max)| {
+                    let mut next = min;
+                    for (low, high, _) in &covered {
+                        if *low > next {
+                            return false;
+                        }
+                        next = high + 1;
+                    }
+                    next > max
+                });
+                if !exhaustive {
+                    return Err(CompileError::new(
+                        "non-exhaustive match, add a `_` arm to cover the remaining values"
+                            .to_string(),
+                    )
+                    .attach_metadata(metadata.clone()));
+                }
+            }
+        }
+        // a table holds one label per value between the lowest and highest pattern,
+        // worthwhile when at least half of those are matched
+        let dense = match (covered.first(), covered.last()) {
+            (Some((min, _, _)), Some((_, max, _))) if covered.len() >= JUMP_TABLE_MIN_ARMS => {
+                let values: i128 = covered.iter().map(|(low, high, _)| high - low + 1).sum();
+                values * 2 > max - min
+            }
+            _ => false,
+        };
+        Ok(dense)
+    }
+}
+
+/// inclusive range of values matched by a literal or range pattern
+fn pattern_bounds(pattern: &ASTnode) -> (i128, i128) {
+    match pattern {
+        ASTnode::Range {
+            start,
+            end,
+            inclusive,
+            ..
+        } => {
+            let start = literal_value(start);
+            let end = literal_value(end);
+            (start, if *inclusive { end } else { end - 1 })
+        }
+        _ => (literal_value(pattern), literal_value(pattern)),
+    }
+}
+
+/// value of an integer or character literal, optionally negated
+fn literal_value(literal: &ASTnode) -> i128 {
+    match literal {
+        ASTnode::NumberLiteral { value, .. } => *value as i128,
+        ASTnode::CharLiteral { value, .. } => *value as i128,
+        ASTnode::BoolLiteral { value, .. } => *value as i128,
+        ASTnode::Negate { value, .. } => -literal_value(value),
+        _ => unreachable!(),
+    }
+}
+
+/// whether control can not reach the end of `node`, every path through it returns or loops forever
+fn always_returns(node: &ASTnode) -> bool {
+    match node {
+        ASTnode::Return { .. } => true,
+        ASTnode::Block { nodes } => nodes.iter().any(always_returns),
+        ASTnode::If {
+            body,
+            else_body: Some(else_body),
+            ..
+        } => always_returns(body) && always_returns(else_body),
+        // a match is exhaustive
+        ASTnode::Match { arms, .. } => arms.iter().all(|arm| match arm {
+            ASTnode::MatchArm { body, .. } => always_returns(body),
+            _ => unreachable!(),
+        }),
+        ASTnode::Loop { body, label, .. } => !breaks_out(body, label.as_deref(), false),
+        _ => false,
+    }
+}
+
+/// whether a `break` in `node` leaves the loop labelled `label` around it, `nested` when `node`
+/// is inside of another loop within that one
+fn breaks_out(node: &ASTnode, label: Option<&str>, nested: bool) -> bool {
+    match node {
+        ASTnode::Break { label: target, .. } => match target {
+            Some(target) => Some(target.as_str()) == label,
+            None => !nested,
+        },
+        ASTnode::Block { nodes } => nodes.iter().any(|node| breaks_out(node, label, nested)),
+        ASTnode::If {
+            body, else_body, ..
+        } => {
+            breaks_out(body, label, nested)
+                || else_body
+                    .as_deref()
+                    .is_some_and(|body| breaks_out(body, label, nested))
+        }
+        ASTnode::Match { arms, .. } => arms.iter().any(|arm| breaks_out(arm, label, nested)),
+        ASTnode::MatchArm { body, .. } => breaks_out(body, label, nested),
+        ASTnode::While { body, .. } | ASTnode::Loop { body, .. } | ASTnode::For { body, .. } => {
+            breaks_out(body, label, true)
+        }
+        _ => false,
+    }
+}
+
+/// whether `kind` is an integer type
+fn is_integer(kind: &ASTnode) -> bool {
+    matches!(kind, ASTnode::Type { name, .. }
+        if PrimitiveType::from_name(name)
+            .is_some_and(|p| p.range().is_some() && p != PrimitiveType::Bool))
+}
+
+fn is_float(kind: &ASTnode) -> bool {
+    matches!(kind, ASTnode::Type { name, .. } if name == "f32")
+}
+
+/// whether `node` is a number literal, possibly negated
+fn is_number_literal(node: &ASTnode) -> bool {
+    match node {
+        ASTnode::NumberLiteral { .. } => true,
+        ASTnode::Negate { value, .. } => is_number_literal(value),
+        _ => false,
+    }
+}
+
+/// name of a struct or enum declaration
+fn type_declaration_name(declaration: &ASTnode) -> Option<&str> {
+    match declaration {
+        ASTnode::StructDeclaration { name, .. } | ASTnode::EnumDeclaration { name, .. } => {
+            Some(name)
+        }
+        _ => None,
+    }
+}
+
+/// every Field of a struct, or of all payloads of an enum
+fn declaration_fields(declaration: &ASTnode) -> Vec<&ASTnode> {
+    match declaration {
+        ASTnode::StructDeclaration { fields, .. } => fields.iter().collect(),
+        ASTnode::EnumDeclaration { variants, .. } => variants
+            .iter()
+            .flat_map(|variant| match variant {
+                ASTnode::EnumVariant { fields, .. } => fields.iter(),
+                _ => unreachable!(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// rejects assignments and address-of on values that do not name a memory location
+fn check_lvalue(node: &ASTnode, metadata: &MetadataReference) -> Result<(), CompileError> {
+    match node {
+        ASTnode::Identifier { .. } | ASTnode::Deref { .. } => Ok(()),
+        ASTnode::FieldAccess { value, .. } | ASTnode::Index { value, .. } => {
+            check_lvalue(value, metadata)
+        }
+        _ => Err(
+            CompileError::new("expression does not name a memory location".to_string())
+                .attach_metadata(metadata.clone()),
+        ),
+    }
+}
+
+/// rejects shifts of values that are not integers, amounts that are not integers and constant
+/// amounts as wide as the value, returns whether a right shift of the value is arithmetic
+fn check_shift(
+    kind: Option<&ASTnode>,
+    amount_kind: Option<&ASTnode>,
+    amount: &ASTnode,
+    metadata: &MetadataReference,
+) -> Result<bool, CompileError> {
+    let primitive = match kind {
+        Some(ASTnode::Type { name, .. }) => PrimitiveType::from_name(name),
+        Some(_) => None,
+        None => PrimitiveType::from_name(DEFAULT_INTEGER_TYPE),
+    };
+    let name = kind.map_or(DEFAULT_INTEGER_TYPE.to_string(), type_name);
+    let Some(primitive) =
+        primitive.filter(|p| *p != PrimitiveType::Float32 && *p != PrimitiveType::Bool)
+    else {
+        return Err(CompileError::new(format!(
+            "cannot shift a value of type `{name}`, it is not an integer"
+        ))
+        .attach_metadata(metadata.clone()));
+    };
+    if let Some(amount_kind) = amount_kind.filter(|kind| !is_integer(kind)) {
+        return Err(CompileError::new(format!(
+            "a shift amount has to be an integer, found `{}`",
+            type_name(amount_kind)
+        ))
+        .attach_metadata(metadata.clone()));
+    }
+    if let ASTnode::NumberLiteral { value, .. } = amount {
+        if *value >= primitive.size() * 8 {
+            return Err(CompileError::new(format!(
+                "shift by {value} is too large for a value of type `{name}`"
+            ))
+            .attach_metadata(metadata.clone()));
+        }
+    }
+    Ok(primitive.is_signed())
+}
+
+/// type as written in source, for error messages
+fn type_name(kind: &ASTnode) -> String {
+    match kind {
+        ASTnode::Type { name, .. } => name.clone(),
+        ASTnode::PointerType { kind, .. } => format!("*{}", type_name(kind)),
+        ASTnode::ArrayType { kind, length, .. } => format!("[{}; {length}]", type_name(kind)),
+        ASTnode::TupleType { elements, .. } => format!(
+            "({})",
+            elements
+                .iter()
+                .map(type_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ASTnode::FunctionType {
+            params,
+            return_type,
+            variadic,
+            ..
+        } => {
+            let mut params = params.iter().map(type_name).collect::<Vec<_>>();
+            if *variadic {
+                params.push("...".to_string());
+            }
+            let params = params.join(", ");
+            match return_type {
+                Some(kind) => format!("fn({params}) -> {}", type_name(kind)),
+                None => format!("fn({params})"),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// named types a type is built from, types behind a pointer are not needed to lay it out
+fn named_types(kind: &ASTnode) -> Vec<&ASTnode> {
+    match kind {
+        ASTnode::Type { .. } => vec![kind],
+        ASTnode::ArrayType { kind, .. } => named_types(kind),
+        ASTnode::TupleType { elements, .. } => elements.iter().flat_map(named_types).collect(),
+        _ => Vec::new(),
+    }
+}
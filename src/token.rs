@@ -5,17 +5,16 @@ use std::{
     io::read_to_string,
 };
 
-use colorize::AnsiColor;
-
 use crate::{
-    constant::{COMMENT_CHAR, SOURCE_FILE_EXTENSION, SYSTEM_LIB_ROOT},
+    constant::{SOURCE_FILE_EXTENSION, SYSTEM_LIB_ROOT},
     verbose_println, very_verbose_println, very_very_verbose_println,
 };
 
 pub struct CompileError {
     error: String,
     metadata: Option<MetadataReference>,
-    token: Option<Token>,
+    // boxed to keep the error, returned by nearly every function, small
+    token: Option<Box<Token>>,
     dereferenced_metadata_str: Option<String>,
 }
 impl CompileError {
@@ -32,7 +31,7 @@ impl CompileError {
         self
     }
     pub fn attach_token(mut self, token: Token) -> Self {
-        self.token = Some(token);
+        self.token = Some(Box::new(token));
         self
     }
     pub fn fmt_metadata(mut self, source: &Source) -> Self {
@@ -46,7 +45,7 @@ impl CompileError {
         let metadata = self
             .metadata
             .clone()
-            .or_else(|| self.token.clone().and_then(|t| Some(t.metadata)));
+            .or_else(|| self.token.clone().map(|t| t.metadata));
 
         if let Some(m) = metadata {
             let line = source.get_line(&m.file_name, m.line_number);
@@ -86,7 +85,7 @@ impl fmt::Display for CompileError {
         let metadata = self
             .metadata
             .clone()
-            .or_else(|| self.token.clone().and_then(|t| Some(t.metadata)));
+            .or_else(|| self.token.clone().map(|t| t.metadata));
         let body = if let Some(m) = metadata {
             let mut b = format!("at {}:{} in file {}", m.line_number, m.column, m.file_name);
             if let Some(deref_met) = &self.dereferenced_metadata_str {
@@ -117,6 +116,9 @@ impl MetadataReference {
             column,
         }
     }
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
 }
 impl fmt::Debug for MetadataReference {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -179,10 +181,11 @@ impl Source {
         self.sources.insert(path.to_string(), src);
         Ok(&self.sources[path])
     }
+    #[allow(dead_code)]
     fn add_file(&mut self, file: SourceFile) {
         self.sources.insert(file.file_name.clone(), file);
     }
-    fn merge(&mut self, other: Self) {
+    pub fn merge(&mut self, other: Self) {
         for (k, v) in other.sources {
             self.sources.insert(k, v);
         }
@@ -212,6 +215,54 @@ pub enum PrimitiveType {
     Signed64,
 
     Float32,
+
+    Bool,
+}
+impl PrimitiveType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "u8" => Some(Self::Unsigned8),
+            "u16" => Some(Self::Unsigned16),
+            "u32" => Some(Self::Unsigned32),
+            "u64" => Some(Self::Unsigned64),
+            "i8" => Some(Self::Signed8),
+            "i16" => Some(Self::Signed16),
+            "i32" => Some(Self::Signed32),
+            "i64" => Some(Self::Signed64),
+            "f32" => Some(Self::Float32),
+            "bool" => Some(Self::Bool),
+            _ => None,
+        }
+    }
+    /// size in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Unsigned8 | Self::Signed8 | Self::Bool => 1,
+            Self::Unsigned16 | Self::Signed16 => 2,
+            Self::Unsigned32 | Self::Signed32 | Self::Float32 => 4,
+            Self::Unsigned64 | Self::Signed64 => 8,
+        }
+    }
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Self::Signed8 | Self::Signed16 | Self::Signed32 | Self::Signed64
+        )
+    }
+    /// inclusive range of values an integer type can hold, None for f32, 0 to 1 for bool
+    pub fn range(&self) -> Option<(i128, i128)> {
+        match self {
+            Self::Float32 => return None,
+            Self::Bool => return Some((0, 1)),
+            _ => (),
+        }
+        let bits = self.size() as u32 * 8;
+        Some(if self.is_signed() {
+            (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+        } else {
+            (0, (1 << bits) - 1)
+        })
+    }
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
@@ -219,6 +270,9 @@ pub enum TokenKind {
     Include,
 
     StringLiteral,
+    CharLiteral,
+    /// 'name, the lexeme holds the name without the apostrophe
+    Label,
     NumberLiteral,
     Identifer,
 
@@ -234,10 +288,21 @@ pub enum TokenKind {
     KWin,
     KWbreak,
     KWcontinue,
+    KWloop,
+    KWasm,
+    KWextern,
+    KWmod,
+    KWuse,
+    KWpub,
+    KWimpl,
     KWstruct,
     KWenum,
     KWconst,
     KWstatic,
+    KWmut,
+    KWas,
+    KWtrue,
+    KWfalse,
     Primitive(String),
 
     Slash,
@@ -251,8 +316,22 @@ pub enum TokenKind {
     At,
     Exclaim,
     Assign,
+    // compound assignment, `+=` ...
+    PlusAssign,
+    DashAssign,
+    StarAssign,
+    SlashAssign,
+    PercentAssign,
+    AndAssign,
+    PipeAssign,
+    CarotAssign,
+    ShiftLeftAssign,
+    ShiftRightAssign,
+    ShiftLeft,
+    ShiftRight,
 
     Colon,
+    DoubleColon,
     SemiColon,
     OpenCurly,
     ClosedCurly,
@@ -262,21 +341,25 @@ pub enum TokenKind {
     ClosedParenth,
     Comma,
     Dot,
+    #[allow(dead_code)]
     Apostro,
     Greater,
     Lesser,
 
     Compare,
+    NotEqual,
     GreaterEq,
     LesserEq,
     DoublePipe,
+    DoubleAnd,
     Arrow,
+    FatArrow,
 
     Pipe,
     BackTick,
-    Grave,
+    Tilde,
 
-    EOF,
+    Eof,
 }
 impl TokenKind {
     fn match_keyword(s: &str) -> Option<Self> {
@@ -292,10 +375,19 @@ impl TokenKind {
             "in" => Some(Self::KWin),
             "break" => Some(Self::KWbreak),
             "continue" => Some(Self::KWcontinue),
+            "loop" => Some(Self::KWloop),
+            "asm" => Some(Self::KWasm),
+            "extern" => Some(Self::KWextern),
+            "mod" => Some(Self::KWmod),
+            "use" => Some(Self::KWuse),
+            "pub" => Some(Self::KWpub),
+            "impl" => Some(Self::KWimpl),
             "struct" => Some(Self::KWstruct),
             "enum" => Some(Self::KWenum),
             "const" => Some(Self::KWconst),
             "static" => Some(Self::KWstatic),
+            "mut" => Some(Self::KWmut),
+            "as" => Some(Self::KWas),
             "u8" => Some(Self::Primitive(s.to_string())),
             "u16" => Some(Self::Primitive(s.to_string())),
             "u32" => Some(Self::Primitive(s.to_string())),
@@ -305,6 +397,9 @@ impl TokenKind {
             "i32" => Some(Self::Primitive(s.to_string())),
             "i64" => Some(Self::Primitive(s.to_string())),
             "f32" => Some(Self::Primitive(s.to_string())),
+            "bool" => Some(Self::Primitive(s.to_string())),
+            "true" => Some(Self::KWtrue),
+            "false" => Some(Self::KWfalse),
 
             "include" => Some(Self::Include),
             _ => None,
@@ -328,7 +423,7 @@ impl Token {
     /// returns EOF sentinel token
     fn eof() -> Self {
         Self {
-            kind: TokenKind::EOF,
+            kind: TokenKind::Eof,
             lexeme: <String as std::default::Default>::default(),
             metadata: MetadataReference::default(),
         }
@@ -366,10 +461,15 @@ enum State {
     Comment,
 
     DoubleCharToken(char),
+    /// `<<` or `>>` which may still be followed by `=`
+    ShiftToken(char),
     BuildingIdentifier,
     BuildingString,
     EndString,
     StrEsc,
+    BuildingChar,
+    CharEsc,
+    BuildingLabel,
 }
 enum Stream {
     Master,
@@ -380,6 +480,8 @@ pub struct TokenStream {
     tokens: VecDeque<Token>,
 
     pub source: Source,
+    /// files of the modules being parsed, outermost first, a module can not contain itself
+    pub modules: Vec<String>,
 }
 impl TokenStream {
     pub fn new() -> Self {
@@ -387,6 +489,7 @@ impl TokenStream {
             tokens: VecDeque::new(),
 
             source: Source::new(),
+            modules: Vec::new(),
         }
     }
     pub fn tokenize_source_tree(&mut self, file_path: &str) -> Result<(), CompileError> {
@@ -406,10 +509,29 @@ impl TokenStream {
     }
 
     pub fn next(&mut self) -> Option<Token> {
+        // the EOF sentinel is never consumed so that callers can keep peeking at it
+        if self.eof() {
+            return self.peek().cloned();
+        }
         self.tokens.pop_front()
     }
+    /// consumes the next token, returning an error built from `msg` if it is not of `kind`
+    pub fn expect(&mut self, kind: TokenKind, msg: &str) -> Result<Token, CompileError> {
+        let token = self.next().unwrap_or_else(Token::eof);
+        if token.is(kind) {
+            Ok(token)
+        } else {
+            Err(CompileError::new(msg.to_string())
+                .attach_token(token)
+                .fmt_metadata(&self.source))
+        }
+    }
     pub fn peek(&self) -> Option<&Token> {
-        self.tokens.get(0)
+        self.tokens.front()
+    }
+    /// looks `n` tokens ahead, `peek_nth(0)` is `peek()`
+    pub fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(n)
     }
     pub fn peek_is(&self, kind: TokenKind) -> bool {
         if let Some(t) = self.peek() {
@@ -420,7 +542,7 @@ impl TokenStream {
     }
 
     pub fn eof(&self) -> bool {
-        self.peek_is(TokenKind::EOF)
+        self.peek_is(TokenKind::Eof)
     }
 }
 
@@ -495,9 +617,9 @@ impl Tokenizer {
                     TokenKind::KWif => command = Command::If,
                     TokenKind::KWelse => command = Command::Else,
                     _ => {
-                        return Err(CompileError::new(format!(
-                            "unexpected token in preprocessor command"
-                        ))
+                        return Err(CompileError::new(
+                            "unexpected token in preprocessor command".to_string(),
+                        )
                         .attach_token(token.clone()))
                     }
                 },
@@ -561,9 +683,8 @@ impl Tokenizer {
         very_very_verbose_println!(">> tokenizing ::{line_n}:: {line}");
         let mut advance = true;
         let mut line_iter = line.chars().enumerate();
-        match self.state {
-            State::Comment => self.state = State::Inital,
-            _ => (),
+        if let State::Comment = self.state {
+            self.state = State::Inital;
         }
         loop {
             // very_very_verbose_println!("STATE : {:?}", self.state);
@@ -648,30 +769,6 @@ impl Tokenizer {
                             );
                             self.push_token(token);
                         }
-                        '+' => {
-                            let token = Token::new(
-                                TokenKind::Plus,
-                                "+",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
-                        '*' => {
-                            let token = Token::new(
-                                TokenKind::Star,
-                                "*",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
-                        ':' => {
-                            let token = Token::new(
-                                TokenKind::Colon,
-                                ":",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
                         ';' => {
                             // self.active_stream = Stream::Master;
                             match self.active_stream {
@@ -694,30 +791,6 @@ impl Tokenizer {
                                 }
                             }
                         }
-                        '&' => {
-                            let token = Token::new(
-                                TokenKind::And,
-                                "&",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
-                        '^' => {
-                            let token = Token::new(
-                                TokenKind::Carot,
-                                "^",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
-                        '%' => {
-                            let token = Token::new(
-                                TokenKind::Percent,
-                                "%",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
                         '$' => {
                             let token = Token::new(
                                 TokenKind::Dollar,
@@ -734,14 +807,6 @@ impl Tokenizer {
                             );
                             self.push_token(token);
                         }
-                        '!' => {
-                            let token = Token::new(
-                                TokenKind::Exclaim,
-                                "!",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
                         '`' => {
                             let token = Token::new(
                                 TokenKind::BackTick,
@@ -752,22 +817,17 @@ impl Tokenizer {
                         }
                         '~' => {
                             let token = Token::new(
-                                TokenKind::Grave,
+                                TokenKind::Tilde,
                                 "~",
                                 MetadataReference::new(file_name, line_n, self.lexeme_column_start),
                             );
                             self.push_token(token);
                         }
-                        '\'' => {
-                            let token = Token::new(
-                                TokenKind::Apostro,
-                                "&",
-                                MetadataReference::new(file_name, line_n, self.lexeme_column_start),
-                            );
-                            self.push_token(token);
-                        }
+                        '\'' => self.state = State::BuildingChar,
 
-                        '<' | '>' | '=' | '|' | '-' => self.state = State::DoubleCharToken(chr),
+                        '<' | '>' | '=' | '|' | '-' | '!' | ':' | '+' | '*' | '&' | '^' | '%' => {
+                            self.state = State::DoubleCharToken(chr)
+                        }
                         ' ' | '\t' => continue,
                         _ => {
                             self.state = State::BuildingIdentifier;
@@ -777,28 +837,29 @@ impl Tokenizer {
                 }
 
                 State::DoubleCharToken(ch2) => {
-                    let kind = match chr {
-                        '=' => match ch2 {
-                            '<' => TokenKind::LesserEq,
-                            '>' => TokenKind::GreaterEq,
-                            '=' => TokenKind::Compare,
-                            '|' => TokenKind::DoublePipe,
-                            '-' => {
-                                advance = false;
-                                TokenKind::Dash
-                            }
-                            // '-' => TokenKind::EqDash,
-                            // '+' => TokenKind::EqPlus,
-                            // '*' => TokenKind::
-                            _ => unreachable!(),
-                        },
-
-                        '>' => match ch2 {
-                            '-' => TokenKind::Arrow,
-                            _ => unreachable!(),
-                        },
-
+                    if let ('<', '<') | ('>', '>') = (ch2, chr) {
+                        self.state = State::ShiftToken(ch2);
+                        continue;
+                    }
+                    let kind = match (ch2, chr) {
+                        ('<', '=') => TokenKind::LesserEq,
+                        ('>', '=') => TokenKind::GreaterEq,
+                        ('=', '=') => TokenKind::Compare,
+                        ('+', '=') => TokenKind::PlusAssign,
+                        ('-', '=') => TokenKind::DashAssign,
+                        ('*', '=') => TokenKind::StarAssign,
+                        ('%', '=') => TokenKind::PercentAssign,
+                        ('&', '=') => TokenKind::AndAssign,
+                        ('|', '=') => TokenKind::PipeAssign,
+                        ('|', '|') => TokenKind::DoublePipe,
+                        ('&', '&') => TokenKind::DoubleAnd,
+                        ('^', '=') => TokenKind::CarotAssign,
+                        ('!', '=') => TokenKind::NotEqual,
+                        ('-', '>') => TokenKind::Arrow,
+                        ('=', '>') => TokenKind::FatArrow,
+                        (':', ':') => TokenKind::DoubleColon,
                         _ => {
+                            // single character token, reprocess chr on the next pass
                             advance = false;
                             match ch2 {
                                 '<' => TokenKind::Lesser,
@@ -806,20 +867,57 @@ impl Tokenizer {
                                 '=' => TokenKind::Assign,
                                 '|' => TokenKind::Pipe,
                                 '-' => TokenKind::Dash,
+                                '!' => TokenKind::Exclaim,
+                                ':' => TokenKind::Colon,
+                                '+' => TokenKind::Plus,
+                                '*' => TokenKind::Star,
+                                '&' => TokenKind::And,
+                                '^' => TokenKind::Carot,
+                                '%' => TokenKind::Percent,
                                 _ => unreachable!(),
                             }
                         }
                     };
+                    let lexeme = if advance {
+                        format!("{ch2}{chr}")
+                    } else {
+                        ch2.to_string()
+                    };
                     let token = Token::new(
                         kind,
-                        ch2.to_string().as_str(),
+                        &lexeme,
                         MetadataReference::new(file_name, line_n, self.lexeme_column_start),
                     );
                     self.push_token(token);
                 }
 
+                State::ShiftToken(ch) => {
+                    let (kind, lexeme) = match (ch, chr) {
+                        ('<', '=') => (TokenKind::ShiftLeftAssign, "<<="),
+                        ('>', '=') => (TokenKind::ShiftRightAssign, ">>="),
+                        ('<', _) => (TokenKind::ShiftLeft, "<<"),
+                        _ => (TokenKind::ShiftRight, ">>"),
+                    };
+                    if chr != '=' {
+                        advance = false;
+                    }
+                    let token = Token::new(
+                        kind,
+                        lexeme,
+                        MetadataReference::new(file_name, line_n, self.lexeme_column_start),
+                    );
+                    self.push_token(token);
+                }
                 State::PreComment => match chr {
                     '/' => self.state = State::Comment,
+                    '=' => {
+                        let token = Token::new(
+                            TokenKind::SlashAssign,
+                            "/=",
+                            MetadataReference::new(file_name, line_n, self.lexeme_column_start),
+                        );
+                        self.push_token(token);
+                    }
                     _ => {
                         advance = false;
                         let token = Token::new(
@@ -837,23 +935,56 @@ impl Tokenizer {
                     _ => self.active_lexeme.push(chr),
                 },
                 State::StrEsc => {
-                    let esc_char = match chr {
-                        '\\' => '\\',
-                        'n' => '\n',
-                        't' => '\t',
-                        'r' => '\r',
-                        '\'' => '\'',
-                        '"' => '"',
-                        '0' => '\0',
-                        'b' => '\x08',
-                        _ => {
+                    let esc_char = escape_char(chr)?;
+                    self.active_lexeme.push(esc_char);
+                    self.state = State::BuildingString;
+                }
+                State::BuildingChar => match chr {
+                    '\'' => {
+                        if self.active_lexeme.chars().count() != 1 {
                             return Err(CompileError::new(format!(
-                                "invalid escape sequence >>\"\\{chr}\"<< "
-                            )))
+                                "character literal must hold exactly one character >>'{}'<< ",
+                                self.active_lexeme
+                            ))
+                            .attach_metadata(MetadataReference::new(
+                                file_name,
+                                line_n,
+                                self.lexeme_column_start,
+                            )));
                         }
-                    };
+                        let token_metadata =
+                            MetadataReference::new(file_name, line_n, self.lexeme_column_start);
+                        let token =
+                            Token::new(TokenKind::CharLiteral, &self.active_lexeme, token_metadata);
+                        self.push_token(token);
+                    }
+                    '\\' => self.state = State::CharEsc,
+                    // 'a followed by anything but a closing apostrophe is a label
+                    _ if self.active_lexeme.chars().count() == 1
+                        && self
+                            .active_lexeme
+                            .starts_with(|c: char| c.is_alphabetic() || c == '_') =>
+                    {
+                        advance = false;
+                        self.state = State::BuildingLabel;
+                    }
+                    _ => self.active_lexeme.push(chr),
+                },
+                State::BuildingLabel => match chr {
+                    c if c.is_alphanumeric() || c == '_' => self.active_lexeme.push(chr),
+                    _ => {
+                        advance = false;
+                        let token_metadata =
+                            MetadataReference::new(file_name, line_n, self.lexeme_column_start);
+                        let token =
+                            Token::new(TokenKind::Label, &self.active_lexeme, token_metadata);
+                        self.push_token(token);
+                    }
+                },
+                State::CharEsc => {
+                    let esc_char = escape_char(chr)?;
                     self.active_lexeme.push(esc_char);
-                    self.state = State::BuildingString;
+                    self.state = State::BuildingChar;
                 }
                 State::BuildingIdentifier => match chr {
                     '_' => self.active_lexeme.push(chr),
@@ -863,8 +994,8 @@ impl Tokenizer {
                     _ => {
                         advance = false;
                         let kind = match self.active_lexeme.chars().nth(0).unwrap() {
-                            c if c.is_digit(10) => TokenKind::NumberLiteral,
-                            _ => match TokenKind::match_keyword(&self.active_lexeme.trim()) {
+                            c if c.is_ascii_digit() => TokenKind::NumberLiteral,
+                            _ => match TokenKind::match_keyword(self.active_lexeme.trim()) {
                                 Some(k) => k,
                                 None => TokenKind::Identifer,
                             },
@@ -877,6 +1008,7 @@ impl Tokenizer {
                     }
                 },
                 State::EndString => {
+                    advance = false;
                     let token_metadata =
                         MetadataReference::new(file_name, line_n, self.lexeme_column_start);
                     let token = Token::new(
@@ -892,3 +1024,21 @@ impl Tokenizer {
         Ok(self.return_token_stream())
     }
 }
+/// character following a `\` in string and character literals
+fn escape_char(chr: char) -> Result<char, CompileError> {
+    Ok(match chr {
+        '\\' => '\\',
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '\'' => '\'',
+        '"' => '"',
+        '0' => '\0',
+        'b' => '\x08',
+        _ => {
+            return Err(CompileError::new(format!(
+                "invalid escape sequence >>\"\\{chr}\"<< "
+            )))
+        }
+    })
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{accepts, compile_files, rejects};
+
+#[test]
+fn literals_and_indexing() {
+    let tree = accepts(
+        "fn f(i: u8) -> u32 { let a: [u32; 3] = [1, 2, 3]; let m: [[u8; 4]; 2] = [[0; 4]; 2]; return a[i]; }",
+    );
+    assert!(tree.contains(
+        "ArrayType { kind: ArrayType { kind: Type { name: \"u8\" }, length: 4 }, length: 2 }"
+    ));
+    assert!(tree.contains("Index { value: Identifier { name: \"a\" }, index: Identifier { name: \"i\" }, checked: false }"));
+}
+
+#[test]
+fn constant_index_out_of_bounds() {
+    rejects(
+        "fn f() { let a: [u8; 4] = [0; 4]; let b = a[4]; }",
+        "index 4 is out of bounds for an array of length 4",
+    );
+}
+
+#[test]
+fn index_that_is_not_an_integer() {
+    rejects(
+        "fn f() { let a: [u8; 4] = [0; 4]; let b = a[true]; }",
+        "an index has to be an integer, found `bool`",
+    );
+}
+
+#[test]
+fn bounds_check_flag_checks_unknown_indices() {
+    let source = "fn f(i: u8) -> u8 { let a: [u8; 4] = [0; 4]; return a[i]; }";
+    assert!(accepts(source).contains("checked: false"));
+    let tree = compile_files(&[("main", source)], &["--bounds-check"]).unwrap();
+    assert!(tree.contains("checked: true"));
+}
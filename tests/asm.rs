@@ -0,0 +1,36 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn asm_block_and_naked_function() {
+    let tree = accepts(
+        "asm fn reset() { \"ld sp, 0x1000\" \"jmp main\" } \
+         fn f(count: u32) -> u32 { let result: u32 = 0; asm (in r1 = count, out r2 = result, clobber r3) { \"mov r3, r1\" \"add r2, r3\" } return result; }",
+    );
+    assert!(tree.contains("naked: true"));
+}
+
+#[test]
+fn register_out_of_range() {
+    rejects(
+        "fn f(a: u8) { asm (in r13 = a) { \"nop\" } }",
+        "`r13` is not a register an asm block can bind, use r1 to r12",
+    );
+}
+
+#[test]
+fn register_bound_twice() {
+    rejects(
+        "fn f(a: u8) { asm (in r1 = a, clobber r1) { \"nop\" } }",
+        "register `r1` is used by more than one operand",
+    );
+}
+
+#[test]
+fn output_to_a_value() {
+    rejects(
+        "fn f() { asm (out r1 = 3) { \"nop\" } }",
+        "expression does not name a memory location",
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn compound_assignment_scales_pointers() {
+    let tree = accepts("fn f(p: *u32, x: i16) { let q = p; q += 2; let y = x; y >>= 1; y *= 3; }");
+    assert!(tree.contains("operator: Add, expr: NumberLiteral { value: 2 }, scale: 4"));
+    assert!(tree.contains("operator: ArithmeticShiftRight"));
+}
+
+#[test]
+fn multiplying_a_pointer() {
+    rejects(
+        "fn f(p: *u32) { let q = p; q *= 2; }",
+        "only `+=` and `-=` can be applied to a pointer",
+    );
+}
+
+#[test]
+fn assigned_value_of_the_wrong_type() {
+    accepts("fn f(a: u8) { let x: u8 = 1; x = a; x = 255; }");
+    rejects(
+        "fn f() { let x: u8 = 1; x = true; }",
+        "expected `u8`, found `bool`",
+    );
+    rejects(
+        "fn f() { let x: u8 = 1; let p: *u8 = &x; x = p; }",
+        "expected `u8`, found `*u8`",
+    );
+    rejects(
+        "fn f() { let x: u8 = 1; x = 256; }",
+        "`256` does not fit in `u8`",
+    );
+}
+
+#[test]
+fn compound_operand_of_the_wrong_type() {
+    rejects(
+        "fn f() { let x: u8 = 1; let p: *u8 = &x; x += p; }",
+        "`+=` takes two values of the same type, found `u8` and `*u8`",
+    );
+    rejects(
+        "fn f(b: bool) { let c = b; c |= b; }",
+        "`|=` takes integers, found `bool`",
+    );
+    rejects(
+        "fn f(p: *u32, b: bool) { let q = p; q -= b; }",
+        "a pointer is moved by an integer, found `bool`",
+    );
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn conditions_and_logical_operators() {
+    let tree = accepts(
+        "fn retry() -> bool { return true; } \
+         fn f(count: u8, busy: bool, reg: u8) { let ready = count != 0 && !busy; if ready || retry() { } while (reg & 0x80) == 0 { } }",
+    );
+    assert!(tree.contains("LogicalAnd"));
+    assert!(tree.contains("LogicalOr"));
+}
+
+#[test]
+fn integer_condition() {
+    rejects(
+        "fn f(count: u8) { if count { } }",
+        "condition must be a `bool`, found `u8`, compare it explicitly such as `!= 0`",
+    );
+}
+
+#[test]
+fn logical_and_on_integers() {
+    rejects(
+        "fn f(a: u8, b: bool) { let c = a && b; }",
+        "`&&` takes a `bool`, found `u8`",
+    );
+}
+
+#[test]
+fn number_where_a_bool_is_expected() {
+    rejects(
+        "fn f() { let b: bool = 1; if b { } }",
+        "expected `bool`, found a number",
+    );
+    rejects(
+        "fn f(a: bool) { let b = a == 1; }",
+        "expected `bool`, found a number",
+    );
+}
+
+#[test]
+fn bitwise_operators_on_bools() {
+    rejects(
+        "fn f(a: bool, b: bool) { let c = a & b; }",
+        "`&` takes integers, found `bool`",
+    );
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn integer_float_and_pointer_casts() {
+    let tree = accepts(
+        "fn f(a: u8, p: *u8) -> u64 { let b = a as u32; let c = -5 as i8 as u8; let x = b as f32; return p as u64; }",
+    );
+    assert!(tree.contains("IntToFloat"));
+}
+
+#[test]
+fn pointer_to_float() {
+    rejects(
+        "fn f(p: *u8) { let x = p as f32; }",
+        "cannot cast a value of type `*u8` as `f32`",
+    );
+}
+
+#[test]
+fn cast_to_bool() {
+    rejects(
+        "fn f(a: u8) { let x = a as bool; }",
+        "cannot cast a value of type `u8` as `bool`",
+    );
+}
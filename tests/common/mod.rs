@@ -0,0 +1,75 @@
+// every test file uses its own part of these helpers
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// runs the compiler with `flags` on `files`, (module name, source) pairs written next to each
+/// other, the first one is the file given to the compiler. gives the printed tree or the error
+pub fn compile_files(files: &[(&str, &str)], flags: &[&str]) -> Result<String, String> {
+    let dir: PathBuf = std::env::temp_dir().join(format!(
+        "ansl-test-{}-{}",
+        std::process::id(),
+        NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    for (name, source) in files {
+        fs::write(dir.join(format!("{name}.ansl")), source).unwrap();
+    }
+    let main = dir.join(format!("{}.ansl", files[0].0));
+    let output = Command::new(env!("CARGO_BIN_EXE_ansl"))
+        .arg(&main)
+        .args(flags)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    if let Some(error) = lines.find(|line| line.starts_with("Error in compilation")) {
+        return Err(error.to_string());
+    }
+    stdout
+        .lines()
+        .find(|line| line.starts_with("Root {"))
+        .map(strip_metadata)
+        .ok_or_else(|| format!("no tree printed:\n{stdout}"))
+}
+
+/// the tree without source locations, `Identifier { name: "x" }`
+fn strip_metadata(tree: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = tree;
+    while let Some(start) = rest.find("metadata: [") {
+        let end = start + rest[start..].find(']').unwrap() + 1;
+        let kept = &rest[..start];
+        stripped += kept.strip_suffix(", ").unwrap_or(kept);
+        rest = &rest[end..];
+    }
+    stripped + rest
+}
+
+pub fn compile(source: &str) -> Result<String, String> {
+    compile_files(&[("main", source)], &[])
+}
+
+/// the tree of `source`, panics with the error when it does not compile
+pub fn accepts(source: &str) -> String {
+    compile(source).unwrap_or_else(|error| panic!("{error}\nin:\n{source}"))
+}
+
+/// panics unless `source` fails to compile with an error containing `message`
+pub fn rejects(source: &str, message: &str) {
+    match compile(source) {
+        Ok(_) => panic!("compiled but expected `{message}`:\n{source}"),
+        Err(error) => assert!(
+            error.contains(message),
+            "expected `{message}`, found: {error}"
+        ),
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn if_else_and_while() {
+    let tree = accepts(
+        "fn f(a: u8) -> u8 { while a < 3 { a = a + 1; } if a == 3 { return 1; } else { return a; } }",
+    );
+    assert!(tree.contains("While { condition: Lesser("));
+    assert!(tree.contains("else_body: Some(Block"));
+}
+
+#[test]
+fn break_outside_of_loop() {
+    rejects("fn f() { break; }", "`break` outside of a loop");
+}
+
+#[test]
+fn condition_is_not_bool() {
+    rejects(
+        "fn f(a: u8) { if a { } }",
+        "condition must be a `bool`, found `u8`",
+    );
+}
+
+#[test]
+fn return_value_type() {
+    accepts("fn f() -> (u8, u16) { return (1, 2); }");
+    rejects(
+        "struct P { x: u8 } fn f() -> u8 { return P { x: 1 }; }",
+        "expected `u8`, found `P`",
+    );
+    rejects(
+        "fn f() -> (u8, u16) { return (1, 2, 3); }",
+        "expected a tuple of 2 elements, found 3",
+    );
+}
+
+#[test]
+fn return_without_value() {
+    accepts("fn f() { return; }");
+    rejects(
+        "fn f() -> u8 { return; }",
+        "`return` needs a value of type `u8`",
+    );
+    rejects(
+        "fn f() { return 1; }",
+        "`return` with a value in a function that returns nothing",
+    );
+}
+
+#[test]
+fn every_path_returns() {
+    accepts(
+        "fn f(a: u8) -> u8 { if a == 0 { return 1; } else { return 2; } } \
+         fn g() -> u8 { loop { } } \
+         fn h(a: u8) -> u8 { match a { 0 => { return 1; } _ => { return 2; } } }",
+    );
+}
+
+#[test]
+fn falling_off_the_end() {
+    rejects(
+        "fn f() -> u8 { }",
+        "`f` returns `u8` but can reach the end of its body without a `return`",
+    );
+    rejects(
+        "fn f(a: u8) -> u8 { if a == 0 { return 1; } }",
+        "`f` returns `u8` but can reach the end of its body without a `return`",
+    );
+    rejects(
+        "fn f() -> u8 { loop { break; } }",
+        "`f` returns `u8` but can reach the end of its body without a `return`",
+    );
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn inferred_and_declared_types() {
+    let tree = accepts("const C: u8 = 1; static mut S: u16 = 2; fn main() { let a = C; S = 3; }");
+    assert!(tree.contains(r#"VariableDeclaration { name: "a", kind: Some(Type { name: "u8" })"#));
+}
+
+#[test]
+fn assignment_to_const() {
+    rejects(
+        "const C: u8 = 1; fn main() { C = 2; }",
+        "cannot assign to `C`",
+    );
+}
+
+#[test]
+fn assignment_to_static_without_mut() {
+    rejects(
+        "static S: u8 = 1; fn main() { S += 2; }",
+        "cannot assign to `S`",
+    );
+}
+
+#[test]
+fn literal_out_of_range() {
+    accepts("fn main() { let a: u8 = 255; let b: i8 = -128; }");
+    rejects(
+        "fn main() { let a: u8 = 300; }",
+        "`300` does not fit in `u8`",
+    );
+    rejects(
+        "fn main() { let a: u32 = -1; }",
+        "`-1` does not fit in `u32`",
+    );
+}
+
+#[test]
+fn initializer_of_another_type() {
+    rejects(
+        "struct P { x: u8 } fn main() { let a: u8 = P { x: 1 }; }",
+        "expected `u8`, found `P`",
+    );
+    rejects(
+        "fn main() { let a: u8 = 1; let b: u16 = a; }",
+        "expected `u16`, found `u8`",
+    );
+}
+
+#[test]
+fn array_initializer_length() {
+    accepts("fn main() { let a: [u8; 3] = [1, 2, 3]; }");
+    rejects(
+        "fn main() { let a: [u8; 3] = [1, 2]; }",
+        "expected an array of 3 elements, found 2",
+    );
+}
+
+#[test]
+fn string_literal_is_a_pointer_to_bytes() {
+    let tree = accepts("fn main() { let s = \"hi\"; }");
+    assert!(tree.contains(
+        r#"VariableDeclaration { name: "s", kind: Some(PointerType { kind: Type { name: "u8" } })"#
+    ));
+    rejects(
+        "fn main() { let t: u8 = \"hi\"; }",
+        "expected `u8`, found `*u8`",
+    );
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn discriminants_count_up_from_the_previous_variant() {
+    let tree = accepts("enum C: u8 { A = 1, B, D = 7, E } fn f() { let c = C::E; }");
+    assert!(tree.contains(r#"EnumVariant { name: "B", value: None, fields: [], discriminant: 2 }"#));
+    assert!(tree.contains(r#"EnumVariant { name: "E", value: None, fields: [], discriminant: 8 }"#));
+}
+
+#[test]
+fn discriminant_overflows_the_tag() {
+    rejects(
+        "enum C: u8 { A = 1, B, D = 255, E }",
+        "discriminant 256 of `C::E` overflows `u8`",
+    );
+}
+
+#[test]
+fn duplicate_discriminant() {
+    rejects(
+        "enum C { A = 1, B = 1 }",
+        "discriminant 1 of `C::B` is already used by `C::A`",
+    );
+}
+
+#[test]
+fn payload_variants_are_constructed_and_matched() {
+    let tree = accepts(
+        "enum Msg { Ping, Data(u8, u16) } \
+         fn f() -> u8 { let m = Msg::Data(1, 2); match m { Msg::Ping => { return 0; } Msg::Data(a, _) => { return a; } } }",
+    );
+    assert!(tree.contains(r#"VariantPattern { segments: ["Msg", "Data"]"#));
+}
+
+#[test]
+fn payloads_of_every_kind_of_type() {
+    accepts(
+        "enum Msg { Ping, Data(*u8), Arr([u8; 2]), Pair((u8, u16)), Call(fn(u8) -> u8) } \
+         fn f(m: Msg) { match m { Msg::Data(p) => { let q: *u8 = p; } _ => {} } }",
+    );
+}
+
+#[test]
+fn match_misses_a_variant() {
+    rejects(
+        "enum Msg { Ping, Data(u8, u16) } fn f(m: Msg) { match m { Msg::Ping => {} } }",
+        "non-exhaustive match, missing Msg::Data",
+    );
+}
+
+#[test]
+fn wrong_number_of_payload_values() {
+    rejects(
+        "enum Msg { Ping, Data(u8, u16) } fn f() { let m = Msg::Data(1); }",
+        "`Msg::Data` holds 2 values",
+    );
+}
+
+#[test]
+fn payload_values_of_the_wrong_type() {
+    rejects(
+        "enum M { Ping, Data(u8, u16) } fn f() { let m = M::Data(true, \"x\"); }",
+        "expected `u8`, found `bool`",
+    );
+    rejects(
+        "enum M { Ping, Data(u8, u16) } fn f() { let m = M::Data(1, 70000); }",
+        "`70000` does not fit in `u16`",
+    );
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    let tree = accepts("fn main() { let x = 1 + 2 * 3 - 4; }");
+    assert!(tree.contains(
+        "Sub(BinaryOP { left: Add(BinaryOP { left: NumberLiteral { value: 1 }, \
+         right: Mult(BinaryOP { left: NumberLiteral { value: 2 }, \
+         right: NumberLiteral { value: 3 } }) }), right: NumberLiteral { value: 4 } })"
+    ));
+}
+
+#[test]
+fn parentheses_group() {
+    let tree = accepts("fn main() { let x = (1 + 2) * 3; }");
+    assert!(tree.contains("Mult(BinaryOP { left: Add("));
+}
+
+#[test]
+fn missing_operand() {
+    rejects("fn main() { let x = 1 + ; }", "expected expression");
+}
+
+#[test]
+fn literals_take_the_type_of_the_other_operand() {
+    accepts("fn f(a: u8, b: u8) -> bool { return a + b * 2 < 200; }");
+}
+
+#[test]
+fn operands_of_different_types() {
+    rejects(
+        "fn f(a: u8, b: u16) { let c = a + b; }",
+        "`+` takes two values of the same type, found `u8` and `u16`",
+    );
+}
+
+#[test]
+fn literal_operand_out_of_range() {
+    rejects(
+        "fn f(a: u8) { let c = a * 300; }",
+        "`300` does not fit in `u8`",
+    );
+}
+
+#[test]
+fn comparing_different_types() {
+    rejects(
+        "fn f(a: u8, b: u16) { let c = a == b; }",
+        "`==` takes two values of the same type, found `u8` and `u16`",
+    );
+}
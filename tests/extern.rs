@@ -0,0 +1,36 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn extern_function_and_static() {
+    let tree = accepts(
+        "extern fn puts(s: *u8) -> i32; extern static mut errno: i32; \
+         fn f() -> i32 { errno = 0; return puts(\"hi\"); }",
+    );
+    assert!(tree.contains("name: \"puts\""));
+}
+
+#[test]
+fn extern_function_with_a_body() {
+    rejects(
+        "extern fn puts(s: *u8) -> i32 { return 0; }",
+        "expected `;`, an extern function has no body",
+    );
+}
+
+#[test]
+fn extern_static_with_a_value() {
+    rejects(
+        "extern static errno: i32 = 0;",
+        "an extern static needs a type and cannot have a value",
+    );
+}
+
+#[test]
+fn extern_call_with_a_mismatched_argument() {
+    rejects(
+        "extern fn putc(c: u8) -> i32; fn f(x: u32) { putc(x); }",
+        "mismatched argument to `putc`, expected `u8`, found `u32`",
+    );
+}
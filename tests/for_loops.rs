@@ -0,0 +1,52 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn variable_takes_the_type_of_the_bounds() {
+    accepts("fn f(n: u8) { for i in rev 0..=n step 2 { let j: u8 = i; } }");
+    rejects(
+        "fn f(n: u8) { for i in 0..n { let j: u16 = i; } }",
+        "expected `u16`, found `u8`",
+    );
+}
+
+#[test]
+fn iterable_is_not_a_range() {
+    rejects(
+        "fn f() { let a: [u8; 2] = [1, 2]; for i in a { } }",
+        "`for` iterates over a range",
+    );
+}
+
+#[test]
+fn bounds_of_different_types() {
+    rejects(
+        "fn f(n: u8, m: u16) { for i in n..m { } }",
+        "range bounds have different types, `u8` and `u16`",
+    );
+}
+
+#[test]
+fn step_is_not_positive() {
+    rejects(
+        "fn f() { for i in 0..10 step 0 { } }",
+        "range step has to be positive",
+    );
+    rejects(
+        "fn f() { for i in 0..10 step -2 { } }",
+        "range step has to be positive",
+    );
+}
+
+#[test]
+fn step_of_another_type() {
+    rejects(
+        "fn f(b: bool) { for i in 0..10 step b { } }",
+        "expected `i32`, found `bool`",
+    );
+    rejects(
+        "fn f(n: u8, s: u16) { for i in 0..n step s { } }",
+        "expected `u8`, found `u16`",
+    );
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{accepts, rejects};
+
+const PRINTF: &str = "@format extern fn printf(format: *u8, ...) -> i32; ";
+
+#[test]
+fn matching_arguments() {
+    accepts(&format!(
+        "{PRINTF}fn f(name: *u8, count: u32, delta: i16, c: u8) {{ \
+         printf(\"%s is %u, %d off, %x %c %% %d\", name, count, delta, count, c, 5); }}"
+    ));
+}
+
+#[test]
+fn unknown_specifier() {
+    rejects(
+        &format!("{PRINTF}fn f(x: u32) {{ printf(\"%q\", x); }}"),
+        "unknown format `%q`",
+    );
+}
+
+#[test]
+fn missing_argument() {
+    rejects(
+        &format!("{PRINTF}fn f(x: u32) {{ printf(\"%u %d\", x); }}"),
+        "missing argument for `%d`",
+    );
+}
+
+#[test]
+fn unused_argument() {
+    rejects(
+        &format!("{PRINTF}fn f(x: u32) {{ printf(\"none\", x); }}"),
+        "argument is not used by the format string",
+    );
+}
+
+#[test]
+fn unsigned_for_a_signed_specifier() {
+    rejects(
+        &format!("{PRINTF}fn f(x: u32) {{ printf(\"%d\", x); }}"),
+        "`%d` takes a signed integer, found `u32`",
+    );
+}
+
+#[test]
+fn lone_percent() {
+    rejects(
+        &format!("{PRINTF}fn f() {{ printf(\"100%\"); }}"),
+        "format string ends in a lone `%`",
+    );
+}
+
+#[test]
+fn string_variable_for_s() {
+    accepts("mod stdio; fn main() { let s = \"hi\"; stdio::printf(\"%s\", s); }");
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn stored_and_called() {
+    let tree = accepts(
+        "fn double(x: u8) -> u16 { return x as u16 * 2; } \
+         static COMMANDS: [fn(u8) -> u16; 1] = [&double]; \
+         fn f() -> u16 { let g = &double; return COMMANDS[0](4) + g(1); }",
+    );
+    assert!(tree.contains("FunctionType"));
+}
+
+#[test]
+fn function_used_as_a_value() {
+    rejects(
+        "fn double(x: u8) -> u16 { return x as u16 * 2; } fn f() { let g = double; }",
+        "`double` is a function, take its address with `&double`",
+    );
+}
+
+#[test]
+fn calling_an_integer() {
+    rejects("fn f(x: u8) { x(1); }", "expression is not callable");
+}
+
+#[test]
+fn literal_argument_through_a_pointer_out_of_range() {
+    rejects(
+        "fn double(x: u8) -> u16 { return x as u16 * 2; } fn f() { let g = &double; g(256); }",
+        "`256` does not fit in `u8`",
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn function_with_typed_parameters() {
+    let tree = accepts("fn add(a: u8, b: u16) -> u32 { return 1; }");
+    assert!(tree.contains(r#"FunctionDefinition { name: "add""#));
+    assert!(tree.contains(r#"Variable { name: "b", kind: Type { name: "u16" }"#));
+}
+
+#[test]
+fn parameter_without_type() {
+    rejects("fn add(a, b: u8) {}", "expected colon after parameter name");
+}
+
+#[test]
+fn unclosed_parameter_list() {
+    rejects(
+        "fn add(a: u8 b: u8) {}",
+        "expected `,` or `)` after parameter",
+    );
+}
+
+#[test]
+fn literal_arguments_fit_their_parameters() {
+    accepts("fn f(a: u8, b: u16) -> u16 { return b; } fn g() -> u16 { return f(255, 300); }");
+}
+
+#[test]
+fn literal_argument_out_of_range() {
+    rejects(
+        "fn f(a: u8, b: u16) {} fn g() { f(300, 5); }",
+        "`300` does not fit in `u8`",
+    );
+}
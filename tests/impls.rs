@@ -0,0 +1,32 @@
+mod common;
+
+use common::{accepts, rejects};
+
+const COUNTER: &str = "struct Counter { count: u32 } \
+    impl Counter { \
+    const START: u32 = 0; \
+    fn new() -> Self { return Self { count: Self::START }; } \
+    fn bump(self: *Counter) { (*self).count += 1; } \
+    fn get(self: Counter) -> u32 { return self.count; } } ";
+
+#[test]
+fn methods_and_associated_items() {
+    let tree = accepts(&format!(
+        "{COUNTER}fn f() -> u32 {{ let c = Counter::new(); c.bump(); let p = &c; p.bump(); return p.get(); }}"
+    ));
+    assert!(tree.contains("name: \"Counter$new\""));
+    assert!(tree.contains(
+        "FunctionCall { name: \"Counter$bump\", params: [AddressOf { value: Identifier { name: \"c\" } }] }"
+    ));
+    assert!(tree.contains(
+        "FunctionCall { name: \"Counter$get\", params: [Deref { value: Identifier { name: \"p\" } }] }"
+    ));
+}
+
+#[test]
+fn method_call_on_a_function_without_self() {
+    rejects(
+        &format!("{COUNTER}fn f() {{ let c = Counter::new(); c.new(); }}"),
+        "`new` has no `self` parameter of type `Counter`, call it as `Counter::new(..)`",
+    );
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn labelled_break_and_continue() {
+    accepts(
+        "fn f() { 'scan: for row in 0..8 { for col in 0..8 { if col == row { continue 'scan; } if col == 7 { break 'scan; } } } loop { break; } }",
+    );
+}
+
+#[test]
+fn break_outside_of_a_loop() {
+    rejects("fn f() { break; }", "`break` outside of a loop");
+}
+
+#[test]
+fn undeclared_label() {
+    rejects(
+        "fn f() { loop { continue 'outer; } }",
+        "use of undeclared label `'outer`",
+    );
+}
+
+#[test]
+fn shadowed_label() {
+    rejects(
+        "fn f() { 'a: loop { 'a: loop { break 'a; } } }",
+        "label `'a` shadows the label of an enclosing loop",
+    );
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn queries_become_constants() {
+    let tree = accepts(
+        "struct Header { tag: u8, len: u32 } \
+         const HEADER_SIZE: u32 = sizeof(Header); \
+         fn f() -> u64 { let len_at = offsetof(Header, len); let a = alignof(Header); return sizeof([Header; 16]) + sizeof(*u8); }",
+    );
+    assert!(tree.contains("value: NumberLiteral { value: 8 }"));
+    assert!(tree.contains("Some(NumberLiteral { value: 4 })"));
+    assert!(tree.contains("NumberLiteral { value: 128 }"));
+    assert!(!tree.contains("SizeOf"));
+}
+
+#[test]
+fn offset_of_a_missing_field() {
+    rejects(
+        "struct Header { tag: u8, len: u32 } fn f() { let x = offsetof(Header, size); }",
+        "type `Header` has no field `size`",
+    );
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn literal_range_and_wildcard_arms() {
+    let tree = accepts("fn f(a: u8) { match a { 0 => 1, 1..=5 => 2, _ => 3 } }");
+    assert!(tree.contains("pattern: Range {"));
+    assert!(tree.contains("pattern: Wildcard"));
+}
+
+#[test]
+fn dense_arms_use_a_jump_table() {
+    let tree = accepts("fn f(a: u8) { match a { 0 => 1, 1 => 2, 2 => 3, 3 => 4, _ => 0 } }");
+    assert!(tree.contains("jump_table: true"));
+}
+
+#[test]
+fn non_exhaustive_match() {
+    rejects(
+        "fn f(a: u8) { match a { 0 => 1, 1..=5 => 2 } }",
+        "non-exhaustive match",
+    );
+}
+
+#[test]
+fn overlapping_arms() {
+    rejects(
+        "fn f(a: u8) { match a { 0..=5 => 1, 3 => 2, _ => 3 } }",
+        "match arm overlaps with the arm at",
+    );
+}
+
+#[test]
+fn arm_after_wildcard() {
+    rejects(
+        "fn f(a: u8) { match a { _ => 1, 3 => 2 } }",
+        "unreachable match arm, it follows a `_` arm",
+    );
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::compile_files;
+
+#[test]
+fn qualified_variant_as_expression_and_pattern() {
+    let tree = compile_files(
+        &[
+            (
+                "main",
+                "mod math; fn f() -> u8 { let c = math::Color::Green; \
+             match c { math::Color::Red => { return 1; } _ => { return math::square(2); } } }",
+            ),
+            (
+                "math",
+                "pub enum Color { Red, Green } pub fn square(x: u8) -> u8 { return x * x; }",
+            ),
+        ],
+        &[],
+    )
+    .unwrap();
+    assert!(tree.contains("segments: [\"math$Color\", \"Green\"]"));
+    assert!(tree.contains("segments: [\"math$Color\", \"Red\"]"));
+    assert!(tree.contains("name: \"math$square\""));
+}
+
+#[test]
+fn private_item_of_another_module() {
+    let error = compile_files(
+        &[
+            ("main", "mod math; fn f() -> u8 { return math::square(2); }"),
+            ("math", "fn square(x: u8) -> u8 { return x * x; }"),
+        ],
+        &[],
+    )
+    .unwrap_err();
+    assert!(error.contains("is private to module"), "{error}");
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn address_of_marks_the_variable_and_arithmetic_scales() {
+    let tree = accepts("fn f() { let x: u32 = 5; let p = &x; *p = 7; let q = p + 1; }");
+    assert!(tree.contains(r#"VariableDeclaration { name: "x", kind: Some(Type { name: "u32" }), value: Some(NumberLiteral { value: 5 }), addressed: true }"#));
+    assert!(tree.contains("PointerOffset { pointer: Identifier { name: \"p\" }, offset: NumberLiteral { value: 1 }, scale: 4, negative: false }"));
+}
+
+#[test]
+fn dereference_of_a_value() {
+    rejects(
+        "fn f() { let x: u8 = 1; let y = *x; }",
+        "cannot dereference a value of type `u8`, it is not a pointer",
+    );
+}
+
+#[test]
+fn arithmetic_between_pointers() {
+    rejects(
+        "fn f() { let x: u8 = 1; let p = &x; let q = p + p; }",
+        "arithmetic between two pointers is not supported",
+    );
+}
+
+#[test]
+fn address_of_a_temporary() {
+    rejects(
+        "fn f() { let p = &(1 + 2); }",
+        "expression does not name a memory location",
+    );
+}
+
+#[test]
+fn integer_minus_pointer() {
+    accepts("fn f(p: *u8, i: u64) { let q = p - i; let r = 2 + p; }");
+    rejects(
+        "fn f(p: *u8) { let q = 1 - p; }",
+        "a pointer can not be subtracted from a value",
+    );
+}
+
+#[test]
+fn offset_that_is_not_an_integer() {
+    rejects(
+        "fn f(p: *u8) { let q = p + true; }",
+        "a pointer is moved by an integer, found `bool`",
+    );
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn shifts_and_bitwise_not() {
+    let tree = accepts(
+        "fn f(mode: u8, mask: u32, reg: u32) -> i16 { let ctrl: u8 = (1 << 7) | (mode << 4); let low = ~mask & reg; return -8 as i16 >> 1; }",
+    );
+    assert!(tree.contains("ArithmeticShiftRight"));
+}
+
+#[test]
+fn shift_by_the_full_width() {
+    rejects(
+        "fn f(a: u8) -> u8 { return a << 8; }",
+        "shift by 8 is too large for a value of type `u8`",
+    );
+}
+
+#[test]
+fn shifting_a_bool() {
+    rejects(
+        "fn f(a: u8) { let b = (a == 1) << 1; }",
+        "cannot shift a value of type `bool`, it is not an integer",
+    );
+}
+
+#[test]
+fn bitwise_not_of_a_bool_or_pointer() {
+    rejects(
+        "fn f() { let a = ~true; }",
+        "`~` takes an integer, found `bool`",
+    );
+    rejects(
+        "fn f(p: *u8) { let a = ~p; }",
+        "`~` takes an integer, found `*u8`",
+    );
+}
+
+#[test]
+fn negating_a_bool_or_pointer() {
+    accepts("fn f(a: i16, x: f32) { let b = -a; let y = -x; }");
+    rejects(
+        "fn f(b: bool) { let c = -b; }",
+        "`-` takes an integer or `f32`, found `bool`",
+    );
+    rejects(
+        "fn f(p: *u8) { let c = -p; }",
+        "`-` takes an integer or `f32`, found `*u8`",
+    );
+}
+
+#[test]
+fn shift_amount_that_is_not_an_integer() {
+    rejects(
+        "fn f(x: u32, p: *u8) { let y = x << p; }",
+        "a shift amount has to be an integer, found `*u8`",
+    );
+    rejects(
+        "fn f(x: u32, b: bool) { let y = x; y >>= b; }",
+        "a shift amount has to be an integer, found `bool`",
+    );
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn fields_are_aligned_and_read() {
+    let tree = accepts(
+        "struct P { x: u8, y: u32 } fn f() -> u32 { let p = P { x: 1, y: 2 }; return p.y; }",
+    );
+    assert!(tree.contains(r#"Field { name: "y", kind: Type { name: "u32" }, offset: 4 }"#));
+    assert!(tree.contains("packed: false, size: 8, align: 4"));
+}
+
+#[test]
+fn packed_struct_has_no_padding() {
+    let tree = accepts("packed struct R { ctrl: u8, data: u32 }");
+    assert!(tree.contains("packed: true, size: 5, align: 1"));
+}
+
+#[test]
+fn unknown_field() {
+    rejects(
+        "struct P { x: u8 } fn f() { let p = P { x: 1 }; let a = p.z; }",
+        "struct `P` has no field named `z`",
+    );
+}
+
+#[test]
+fn missing_field_in_literal() {
+    rejects(
+        "struct P { x: u8, y: u8 } fn f() { let p = P { x: 1 }; }",
+        "missing fields in initializer of `P`: y",
+    );
+}
+
+#[test]
+fn recursive_struct() {
+    rejects(
+        "struct A { b: B } struct B { a: A }",
+        "recursive type `A` has infinite size",
+    );
+}
+
+#[test]
+fn adding_structs() {
+    rejects(
+        "struct P { x: u8 } fn f(a: P, b: P) { let c = a + b; }",
+        "`+` takes integers or `f32`, found `P`",
+    );
+}
+
+#[test]
+fn comparing_structs() {
+    rejects(
+        "struct P { x: u8 } fn f(a: P, b: P) { let c = a == b; }",
+        "`==` cannot compare values of type `P`",
+    );
+}
+
+#[test]
+fn field_values_of_the_wrong_type() {
+    accepts("struct P { a: u8, b: u16 } fn f() { let p = P { a: 255, b: 300 }; }");
+    rejects(
+        "struct P { a: u8, b: u16 } fn f() { let p = P { a: true, b: 1 }; }",
+        "expected `u8`, found `bool`",
+    );
+    rejects(
+        "struct P { a: u8, b: u16 } fn f() { let p = P { a: 1, b: \"hi\" }; }",
+        "expected `u16`, found `*u8`",
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn tuple_return_and_destructuring() {
+    let tree = accepts(
+        "fn divmod(a: u32, b: u32) -> (u32, u32) { return (a / b, a % b); } \
+         fn f() -> u32 { let (q, _) = divmod(7, 2); let t = divmod(9, 4); return q + t.1; }",
+    );
+    assert!(tree.contains("return_type: Some(TupleType { elements: [Type { name: \"u32\" }, Type { name: \"u32\" }] })"));
+}
+
+#[test]
+fn destructuring_a_value_that_is_not_a_tuple() {
+    rejects(
+        "fn f() { let (a, b) = 1; }",
+        "cannot destructure a value of type `i32`, it is not a tuple",
+    );
+}
+
+#[test]
+fn destructuring_the_wrong_number_of_elements() {
+    rejects(
+        "fn f() { let (a, b) = (1, 2, 3); }",
+        "expected a tuple of 2 elements, found 3",
+    );
+}
+
+#[test]
+fn element_past_the_end() {
+    rejects(
+        "fn f() { let t = (1, 2); let x = t.5; }",
+        "tuple of 2 elements has no field `5`",
+    );
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{accepts, rejects};
+
+#[test]
+fn variadic_definition_and_call() {
+    let tree = accepts(
+        "fn sum(count: u8, ...) -> u64 { let args = va_start(); let total: u64 = 0; \
+         while count > 0 { total += va_arg(args, u64); count -= 1; } return total; } \
+         fn f() -> u64 { return sum(3, 1, 2, 3); }",
+    );
+    assert!(tree.contains("variadic: true"));
+}
+
+#[test]
+fn va_start_in_a_fixed_function() {
+    rejects(
+        "fn f(count: u8) { let args = va_start(); }",
+        "`va_start` can only be used in a function taking `...`",
+    );
+}
+
+#[test]
+fn ellipsis_before_a_parameter() {
+    rejects(
+        "fn f(..., count: u8) { }",
+        "`...` has to be the last parameter",
+    );
+}
+
+#[test]
+fn too_few_arguments() {
+    rejects(
+        "fn sum(count: u8, ...) -> u64 { return 0; } fn f() -> u64 { return sum(); }",
+        "wrong number of arguments to `sum`, expected at least 1, found 0",
+    );
+}